@@ -0,0 +1,33 @@
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use hjkl_snake::{GameConfig, GameState};
+
+/// Ticks timed per iteration; the board leaves room for all of them.
+const TICKS: usize = 100;
+
+fn tick_throughput(c: &mut Criterion) {
+    let cfg = GameConfig::builder().width(200).height(200).build();
+    let mut group = c.benchmark_group("tick");
+    group.throughput(criterion::Throughput::Elements(TICKS as u64));
+    for len in [10, 100, 1000] {
+        group.bench_function(format!("len_{len}"), |b| {
+            b.iter_batched(
+                || {
+                    let mut game = GameState::with_seed(cfg.clone(), 42);
+                    game.fill_snake_for_bench(len);
+                    game
+                },
+                |mut game| {
+                    for _ in 0..TICKS {
+                        game.tick();
+                    }
+                    game
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tick_throughput);
+criterion_main!(benches);
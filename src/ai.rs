@@ -0,0 +1,380 @@
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, VecDeque};
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use super::{DIRECTIONS, Direction, GameState, Point};
+
+/// Steer toward the nearest food, skipping moves that would die next tick.
+///
+/// Never suggests a 180° turn. Returns `None` when every move is fatal.
+pub fn greedy_policy(state: &GameState) -> Option<Direction> {
+    let cfg = state.config();
+    DIRECTIONS
+        .into_iter()
+        .filter(|&d| !d.is_opposite(state.player().dir))
+        .filter_map(|d| state.resolve_move(d).ok().map(|p| (d, p)))
+        .min_by_key(|&(d, p)| {
+            let to_food = state
+                .food_positions()
+                .map(|&f| cfg.wrapped_distance(p, f))
+                .min()
+                .unwrap_or(0);
+            // Prefer going straight when distances tie.
+            (to_food, d != state.player().dir)
+        })
+        .map(|(d, _)| d)
+}
+
+/// Follow the shortest safe path to the nearest food.
+///
+/// Body segments and obstacles are treated as blocked for the whole search.
+/// When no food is reachable this falls back to any move that survives the
+/// next tick, returning `None` only if every move is fatal.
+pub fn bfs_policy(state: &GameState) -> Option<Direction> {
+    let blocked: BTreeSet<Point> = state.all_segments().copied().collect();
+    let mut visited = BTreeSet::from([state.head()]);
+    let mut queue = VecDeque::new();
+    let mut fallback = None;
+
+    for d in DIRECTIONS
+        .into_iter()
+        .filter(|&d| !d.is_opposite(state.player().dir))
+    {
+        if let Ok(p) = state.resolve_move(d) {
+            fallback.get_or_insert(d);
+            if visited.insert(p) {
+                queue.push_back((p, d));
+            }
+        }
+    }
+
+    while let Some((p, first)) = queue.pop_front() {
+        if state.food.contains_key(&p) {
+            return Some(first);
+        }
+        for d in DIRECTIONS {
+            let Some(n) = state.step_from(p, d) else {
+                continue;
+            };
+            if !blocked.contains(&n) && !state.obstacles.contains(&n) && visited.insert(n) {
+                queue.push_back((n, first));
+            }
+        }
+    }
+    fallback
+}
+
+/// Chase the player's own tail, which keeps opening up room behind it, to
+/// stay alive when no food can be reached safely.
+///
+/// Picks the safe move with the shortest path to the tail's current cell,
+/// preferring to go straight on ties. If the tail can't be reached this
+/// falls back to [`bfs_policy`].
+pub fn survival_policy(state: &GameState) -> Option<Direction> {
+    let player = state.player();
+    let tail = *player.body.back()?;
+    let mut blocked: BTreeSet<Point> = state.all_segments().copied().collect();
+    blocked.remove(&tail);
+
+    DIRECTIONS
+        .into_iter()
+        .filter(|&d| !d.is_opposite(player.dir))
+        .filter_map(|d| state.resolve_move(d).ok().map(|p| (d, p)))
+        .filter_map(|(d, p)| steps_between(state, p, tail, &blocked).map(|n| (d, n)))
+        .min_by_key(|&(d, n)| (n, d != player.dir))
+        .map(|(d, _)| d)
+        .or_else(|| bfs_policy(state))
+}
+
+/// Fewest moves from `from` to `to` avoiding `blocked` and obstacles.
+fn steps_between(
+    state: &GameState,
+    from: Point,
+    to: Point,
+    blocked: &BTreeSet<Point>,
+) -> Option<u32> {
+    let mut visited = BTreeSet::from([from]);
+    let mut queue = VecDeque::from([(from, 0)]);
+    while let Some((p, n)) = queue.pop_front() {
+        if p == to {
+            return Some(n);
+        }
+        for d in DIRECTIONS {
+            let Some(next) = state.step_from(p, d) else {
+                continue;
+            };
+            if !blocked.contains(&next) && !state.obstacles.contains(&next) && visited.insert(next)
+            {
+                queue.push_back((next, n + 1));
+            }
+        }
+    }
+    None
+}
+
+/// The moves along a shortest safe path from the player's head to `goal`,
+/// found with A* under [`crate::GameConfig::wrapped_distance`].
+///
+/// Blocking works as in [`bfs_policy`], and the first move is never a 180°
+/// turn. Returns an empty path if the head is already on `goal`, and `None`
+/// when `goal` can't be reached.
+pub fn astar_to(state: &GameState, goal: Point) -> Option<Vec<Direction>> {
+    let head = state.head();
+    if head == goal {
+        return Some(Vec::new());
+    }
+    let cfg = state.config();
+    let blocked: BTreeSet<Point> = state.all_segments().copied().collect();
+    let mut cost = BTreeMap::from([(head, 0u32)]);
+    let mut came_from: BTreeMap<Point, (Point, Direction)> = BTreeMap::new();
+    let mut open = BinaryHeap::new();
+
+    for d in DIRECTIONS
+        .into_iter()
+        .filter(|&d| !d.is_opposite(state.player().dir))
+    {
+        if let Ok(p) = state.resolve_move(d)
+            && !cost.contains_key(&p)
+        {
+            cost.insert(p, 1);
+            came_from.insert(p, (head, d));
+            open.push(Reverse((1 + cfg.wrapped_distance(p, goal), 1, p)));
+        }
+    }
+
+    while let Some(Reverse((_, g, p))) = open.pop() {
+        if p == goal {
+            let mut path = Vec::new();
+            let mut at = p;
+            while let Some(&(prev, d)) = came_from.get(&at) {
+                path.push(d);
+                at = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if g > cost[&p] {
+            continue; // A cheaper route here was already expanded.
+        }
+        for d in DIRECTIONS {
+            let Some(n) = state.step_from(p, d) else {
+                continue;
+            };
+            if blocked.contains(&n) || state.obstacles.contains(&n) {
+                continue;
+            }
+            let ng = g + 1;
+            if cost.get(&n).is_none_or(|&c| ng < c) {
+                cost.insert(n, ng);
+                came_from.insert(n, (p, d));
+                open.push(Reverse((ng + cfg.wrapped_distance(n, goal), ng, n)));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameConfig, GameStatus, Point, WallBehavior};
+
+    fn open_board() -> GameState {
+        let cfg = GameConfig::builder()
+            .width(10)
+            .height(8)
+            .initial_len(3)
+            .build();
+        let mut g = GameState::with_seed(cfg, 42);
+        g.clear_food();
+        g
+    }
+
+    #[test]
+    fn greedy_moves_closer_to_food() {
+        let mut g = open_board();
+        let food = Point::new(5, 1);
+        assert!(g.place_food(food));
+        let cfg = g.config().clone();
+        for _ in 0..3 {
+            let before = cfg.wrapped_distance(g.head(), food);
+            let dir = greedy_policy(&g).unwrap();
+            g.queue_direction(dir);
+            g.tick();
+            assert!(cfg.wrapped_distance(g.head(), food) < before);
+        }
+        assert_eq!(g.head(), food);
+    }
+
+    #[test]
+    fn greedy_never_reverses() {
+        let mut g = open_board();
+        // Food directly behind the body tempts a 180° turn.
+        assert!(g.place_food(Point::new(1, 4)));
+        let dir = greedy_policy(&g).unwrap();
+        assert!(!dir.is_opposite(Direction::Right));
+    }
+
+    #[test]
+    fn greedy_avoids_walls() {
+        let mut g = open_board();
+        g.player_mut().body.clear();
+        g.player_mut().body.push_back(Point::new(9, 0));
+        // Food beyond the wall would pull a naive policy off-board.
+        assert!(g.place_food(Point::new(0, 0)));
+        let dir = greedy_policy(&g).unwrap();
+        assert_eq!(dir, Direction::Down);
+    }
+
+    fn walled_board() -> GameState {
+        let cfg = GameConfig::builder()
+            .width(6)
+            .height(6)
+            .initial_len(1)
+            .build();
+        let mut g = GameState::with_seed(cfg, 1);
+        g.clear_food();
+        g.player_mut().body.clear();
+        g.player_mut().body.push_back(Point::new(2, 2)); // head, heading right
+        g.player_mut().body.push_back(Point::new(1, 2));
+        g.player_mut().body.push_back(Point::new(0, 2));
+        g
+    }
+
+    #[test]
+    fn bfs_takes_shortest_path_around_wall() {
+        let mut g = walled_board();
+        // A wall in column 3 with a gap only at the top row.
+        for y in 1..6 {
+            g.add_obstacle(Point::new(3, y));
+        }
+        assert!(g.place_food(Point::new(4, 2)));
+        assert_eq!(bfs_policy(&g), Some(Direction::Up));
+
+        // Following the policy reaches the food in the optimal six moves.
+        for _ in 0..6 {
+            let dir = bfs_policy(&g).unwrap();
+            g.queue_direction(dir);
+            g.tick();
+        }
+        assert_eq!(g.score(), 1);
+    }
+
+    #[test]
+    fn bfs_survives_when_food_unreachable() {
+        let mut g = walled_board();
+        assert!(g.place_food(Point::new(5, 5)));
+        g.add_obstacle(Point::new(4, 5));
+        g.add_obstacle(Point::new(5, 4));
+        let dir = bfs_policy(&g).expect("a safe move exists");
+        assert!(g.resolve_move(dir).is_ok());
+    }
+
+    #[test]
+    fn bfs_returns_none_when_boxed_in() {
+        let mut g = walled_board();
+        g.add_obstacle(Point::new(2, 1));
+        g.add_obstacle(Point::new(2, 3));
+        g.add_obstacle(Point::new(3, 2));
+        assert_eq!(bfs_policy(&g), None);
+    }
+
+    #[test]
+    fn survival_circles_a_nearly_full_board() {
+        let cfg = GameConfig::builder()
+            .width(4)
+            .height(4)
+            .initial_len(1)
+            .build();
+        let mut g = GameState::with_seed(cfg, 1);
+        // The snake fills the outer ring, head at (0, 0) heading up with its
+        // tail just to the right; food sits in the 2x2 hole in the middle.
+        let ring = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (1, 3),
+            (2, 3),
+            (3, 3),
+            (3, 2),
+            (3, 1),
+            (3, 0),
+            (2, 0),
+            (1, 0),
+        ];
+        g.player_mut().body = ring.into_iter().map(|(x, y)| Point::new(x, y)).collect();
+        g.player_mut().dir = Direction::Up;
+        g.clear_food();
+        assert!(g.place_food(Point::new(1, 1)));
+
+        for tick in 0..40 {
+            let dir = survival_policy(&g).expect("the tail is always reachable");
+            g.tick_with(Some(dir));
+            assert_eq!(g.status(), GameStatus::Running, "died on tick {tick}");
+        }
+        assert_eq!(g.score(), 0);
+    }
+
+    #[test]
+    fn survival_returns_none_when_boxed_in() {
+        let mut g = walled_board();
+        g.add_obstacle(Point::new(2, 1));
+        g.add_obstacle(Point::new(2, 3));
+        g.add_obstacle(Point::new(3, 2));
+        assert_eq!(survival_policy(&g), None);
+    }
+
+    #[test]
+    fn astar_finds_the_optimum_through_a_maze() {
+        let maze = "\
+            O.#....
+            o.#.#..
+            ..#.#..
+            ....#..
+            ###....";
+        let mut g = GameState::from_ascii(GameConfig::default(), maze).unwrap();
+        g.clear_food();
+        let goal = Point::new(3, 0);
+        // Down column 1, through the gap at (2, 3), then up column 3.
+        let path = astar_to(&g, goal).expect("goal is reachable");
+        assert_eq!(path.len(), 9);
+        for dir in path {
+            g.tick_with(Some(dir));
+        }
+        assert_eq!(g.head(), goal);
+        assert_eq!(g.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn astar_uses_wrapped_edges() {
+        let cfg = GameConfig::builder()
+            .width(6)
+            .height(6)
+            .initial_len(3)
+            .start_pos(Some(Point::new(1, 2)))
+            .start_dir(Direction::Left)
+            .build();
+        let goal = Point::new(5, 2);
+        // Without wrapping the body at (2..=3, 2) forces a detour up and over.
+        let g = GameState::with_seed(cfg.clone(), 1);
+        assert_eq!(astar_to(&g, goal).map(|p| p.len()), Some(6));
+
+        let mut wrapped = cfg;
+        wrapped.wall_behavior = WallBehavior::Wrap;
+        let g = GameState::with_seed(wrapped, 1);
+        assert_eq!(
+            astar_to(&g, goal),
+            Some(alloc::vec![Direction::Left, Direction::Left])
+        );
+    }
+
+    #[test]
+    fn astar_returns_none_when_unreachable() {
+        let mut g = walled_board();
+        g.add_obstacle(Point::new(4, 5));
+        g.add_obstacle(Point::new(5, 4));
+        assert_eq!(astar_to(&g, Point::new(5, 5)), None);
+        assert_eq!(astar_to(&g, g.head()), Some(Vec::new()));
+    }
+}
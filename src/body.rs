@@ -0,0 +1,181 @@
+use alloc::collections::{BTreeMap, VecDeque, vec_deque};
+
+use super::Point;
+
+/// The snake's segments, head first, plus an index of the cells they cover so
+/// occupancy checks don't have to walk the whole body.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "VecDeque<Point>", into = "VecDeque<Point>")
+)]
+pub(crate) struct Body {
+    segments: VecDeque<Point>,
+    /// Segments per cell; more than one only if the body overlaps itself.
+    cells: BTreeMap<Point, u32>,
+}
+
+impl Body {
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn front(&self) -> Option<&Point> {
+        self.segments.front()
+    }
+
+    pub fn back(&self) -> Option<&Point> {
+        self.segments.back()
+    }
+
+    pub fn iter(&self) -> vec_deque::Iter<'_, Point> {
+        self.segments.iter()
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        self.cells.contains_key(p)
+    }
+
+    /// Whether `p` is covered by a segment other than the last `skip`.
+    pub fn contains_before_last(&self, p: &Point, skip: usize) -> bool {
+        self.cells.get(p).is_some_and(|&n| {
+            let skipped = self.segments.iter().rev().take(skip).filter(|&s| s == p);
+            n as usize > skipped.count()
+        })
+    }
+
+    pub fn push_front(&mut self, p: Point) {
+        self.segments.push_front(p);
+        self.add(p);
+    }
+
+    pub fn push_back(&mut self, p: Point) {
+        self.segments.push_back(p);
+        self.add(p);
+    }
+
+    pub fn pop_back(&mut self) -> Option<Point> {
+        let p = self.segments.pop_back()?;
+        self.remove(p);
+        Some(p)
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        while self.segments.len() > len {
+            self.pop_back();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.segments.clear();
+        self.cells.clear();
+    }
+
+    fn add(&mut self, p: Point) {
+        *self.cells.entry(p).or_insert(0) += 1;
+    }
+
+    fn remove(&mut self, p: Point) {
+        if let Some(n) = self.cells.get_mut(&p) {
+            *n -= 1;
+            if *n == 0 {
+                self.cells.remove(&p);
+            }
+        }
+    }
+}
+
+impl PartialEq for Body {
+    fn eq(&self, other: &Self) -> bool {
+        self.segments == other.segments
+    }
+}
+
+impl Eq for Body {}
+
+impl FromIterator<Point> for Body {
+    fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Self {
+        let mut body = Self::default();
+        for p in iter {
+            body.push_back(p);
+        }
+        body
+    }
+}
+
+impl From<VecDeque<Point>> for Body {
+    fn from(segments: VecDeque<Point>) -> Self {
+        segments.into_iter().collect()
+    }
+}
+
+impl<const N: usize> From<[Point; N]> for Body {
+    fn from(segments: [Point; N]) -> Self {
+        segments.into_iter().collect()
+    }
+}
+
+impl From<Body> for VecDeque<Point> {
+    fn from(body: Body) -> Self {
+        body.segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    /// The linear scan `collides_with_body` used before the cell index existed.
+    fn linear_collides(segments: &[Point], p: Point, skip: usize) -> bool {
+        let n = segments.len().saturating_sub(skip);
+        segments.iter().take(n).any(|&s| s == p)
+    }
+
+    #[test]
+    fn index_matches_linear_scan_on_random_bodies() {
+        let mut rng = ChaCha8Rng::seed_from_u64(9);
+        for _ in 0..200 {
+            // Random walk on a small board so bodies cross themselves often.
+            let mut p = Point::new(rng.random_range(0..6), rng.random_range(0..6));
+            let mut body = Body::default();
+            for _ in 0..rng.random_range(1..30) {
+                body.push_back(p);
+                p = Point::new(
+                    (p.x + rng.random_range(-1..=1)).rem_euclid(6),
+                    (p.y + rng.random_range(-1..=1)).rem_euclid(6),
+                );
+            }
+            for _ in 0..rng.random_range(0..5) {
+                body.pop_back();
+            }
+            let segments: Vec<Point> = body.iter().copied().collect();
+            for y in 0..6 {
+                for x in 0..6 {
+                    let q = Point::new(x, y);
+                    assert_eq!(body.contains(&q), linear_collides(&segments, q, 0));
+                    for skip in 1..4 {
+                        assert_eq!(
+                            body.contains_before_last(&q, skip),
+                            linear_collides(&segments, q, skip)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn truncate_and_clear_keep_index_in_sync() {
+        let mut body: Body = [Point::new(0, 0), Point::new(1, 0), Point::new(2, 0)].into();
+        body.truncate(1);
+        assert!(body.contains(&Point::new(0, 0)));
+        assert!(!body.contains(&Point::new(2, 0)));
+        body.clear();
+        assert!(!body.contains(&Point::new(0, 0)));
+        assert_eq!(body.len(), 0);
+    }
+}
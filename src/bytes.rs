@@ -0,0 +1,463 @@
+//! Compact binary snapshots, for network sync and replay storage where
+//! [`GameState::to_json`] is too verbose.
+//!
+//! All integers are little-endian. After a version byte come the board size,
+//! status, RNG seed, stream and word position, the counters, then the snakes,
+//! food and obstacles, each list prefixed with its `u32` length. Optional
+//! points are a `0` byte, or a `1` byte and the point.
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec::Vec;
+use core::fmt;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::{
+    Body, Coord, DIRECTIONS, DeathCause, FoodKind, GameConfig, GameRng, GameState, GameStatus,
+    Point, Snake,
+};
+
+/// Bumped whenever the encoding changes.
+const VERSION: u8 = 2;
+
+/// Largest board [`GameState::from_bytes`] accepts, in cells.
+const MAX_CELLS: u64 = 1 << 24;
+
+const STATUSES: [GameStatus; 4] = [
+    GameStatus::Running,
+    GameStatus::Paused,
+    GameStatus::Dead,
+    GameStatus::Won,
+];
+
+const DEATH_CAUSES: [DeathCause; 6] = [
+    DeathCause::Wall,
+    DeathCause::SelfCollision,
+    DeathCause::Obstacle,
+    DeathCause::Starvation,
+    DeathCause::OtherSnake,
+    DeathCause::HeadOn,
+];
+
+const FOOD_KINDS: [FoodKind; 2] = [FoodKind::Normal, FoodKind::Shrink];
+
+/// Why [`GameState::from_bytes`] rejected its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended partway through the snapshot.
+    UnexpectedEnd,
+    /// Written by a format version this build doesn't know.
+    UnknownVersion(u8),
+    /// An enum tag (status, heading, food kind or death cause) is out of range.
+    BadTag(u8),
+    /// The board has no cells, or more than 2^24.
+    BadDimensions { width: Coord, height: Coord },
+    /// A snake segment, food or obstacle lies off the board.
+    OutOfBounds(Point),
+    /// There are no snakes, or one has no segments.
+    EmptySnake,
+    /// Bytes are left over after the snapshot.
+    TrailingBytes,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("snapshot is truncated"),
+            Self::UnknownVersion(v) => write!(f, "unknown snapshot version {v}"),
+            Self::BadTag(t) => write!(f, "invalid tag byte {t}"),
+            Self::BadDimensions { width, height } => {
+                write!(f, "invalid board size {width}x{height}")
+            }
+            Self::OutOfBounds(p) => write!(f, "({}, {}) is off the board", p.x, p.y),
+            Self::EmptySnake => f.write_str("snapshot has an empty snake"),
+            Self::TrailingBytes => f.write_str("unexpected bytes after snapshot"),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// Why [`GameState::to_bytes`] couldn't encode a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The game draws from a custom RNG ([`GameState::with_boxed_rng`]),
+    /// whose position can't be saved.
+    CustomRng,
+    /// A length or pending growth doesn't fit the format's `u32`.
+    TooLarge(usize),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CustomRng => f.write_str("a custom RNG can't be encoded"),
+            Self::TooLarge(n) => write!(f, "{n} doesn't fit in a u32"),
+        }
+    }
+}
+
+impl core::error::Error for EncodeError {}
+
+impl GameState {
+    /// Encode the board, snakes, food, counters and RNG position compactly.
+    ///
+    /// Settings, portals, the playable mask, the food queue and queued input
+    /// are not included; [`GameState::from_bytes`] takes them from its config.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        let GameRng::ChaCha(rng) = &self.rng else {
+            return Err(EncodeError::CustomRng);
+        };
+        let mut out = Vec::new();
+        out.push(VERSION);
+        put_i32(&mut out, self.cfg.width);
+        put_i32(&mut out, self.cfg.height);
+        out.push(tag_of(&STATUSES, self.status));
+        out.extend_from_slice(&rng.get_seed());
+        out.extend_from_slice(&rng.get_stream().to_le_bytes());
+        out.extend_from_slice(&rng.get_word_pos().to_le_bytes());
+        put_u32(&mut out, self.high_score);
+        out.extend_from_slice(&self.ticks.to_le_bytes());
+        put_u32(&mut out, self.foods_spawned);
+        put_u32(&mut out, self.foods_eaten);
+        out.extend_from_slice(&self.ticks_since_food.to_le_bytes());
+
+        put_len(&mut out, self.snakes.len())?;
+        for snake in &self.snakes {
+            out.push(tag_of(&DIRECTIONS, snake.dir));
+            put_u32(&mut out, snake.score);
+            put_len(&mut out, snake.pending_growth)?;
+            out.push(
+                snake
+                    .death_cause
+                    .map_or(0, |c| tag_of(&DEATH_CAUSES, c) + 1),
+            );
+            match snake.crash_site {
+                None => out.push(0),
+                Some(p) => {
+                    out.push(1);
+                    put_point(&mut out, p);
+                }
+            }
+            let (head, dir, len) = snake.spawn;
+            put_point(&mut out, head);
+            out.push(tag_of(&DIRECTIONS, dir));
+            put_len(&mut out, len)?;
+            put_len(&mut out, snake.body.len())?;
+            for &p in snake.body.iter() {
+                put_point(&mut out, p);
+            }
+        }
+
+        put_len(&mut out, self.food.len())?;
+        for (&p, &kind) in &self.food {
+            put_point(&mut out, p);
+            out.push(tag_of(&FOOD_KINDS, kind));
+            out.extend_from_slice(&self.food_spawn_tick(&p).to_le_bytes());
+        }
+        put_len(&mut out, self.obstacles.len())?;
+        for &p in &self.obstacles {
+            put_point(&mut out, p);
+        }
+        Ok(out)
+    }
+
+    /// Restore a game saved with [`GameState::to_bytes`].
+    ///
+    /// The snapshot's board size overrides `cfg.width`/`cfg.height`; every
+    /// other setting comes from `cfg`. Malformed input is an error, never a
+    /// panic.
+    pub fn from_bytes(mut cfg: GameConfig, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader { bytes };
+        let version = r.u8()?;
+        if version != VERSION {
+            return Err(DecodeError::UnknownVersion(version));
+        }
+        let (width, height) = (r.i32()?, r.i32()?);
+        if width <= 0 || height <= 0 || width as u64 * height as u64 > MAX_CELLS {
+            return Err(DecodeError::BadDimensions { width, height });
+        }
+        cfg.width = width;
+        cfg.height = height;
+        let on_board = |p: Point| {
+            if p.x < 0 || p.x >= width || p.y < 0 || p.y >= height {
+                Err(DecodeError::OutOfBounds(p))
+            } else {
+                Ok(p)
+            }
+        };
+
+        let status = from_tag(&STATUSES, r.u8()?)?;
+        let mut rng = ChaCha8Rng::from_seed(r.take()?);
+        rng.set_stream(u64::from_le_bytes(r.take()?));
+        rng.set_word_pos(u128::from_le_bytes(r.take()?));
+        let high_score = r.u32()?;
+        let ticks = u64::from_le_bytes(r.take()?);
+        let foods_spawned = r.u32()?;
+        let foods_eaten = r.u32()?;
+        let ticks_since_food = u64::from_le_bytes(r.take()?);
+
+        let mut snakes = Vec::new();
+        for _ in 0..r.u32()? {
+            let dir = from_tag(&DIRECTIONS, r.u8()?)?;
+            let score = r.u32()?;
+            let pending_growth = r.u32()? as usize;
+            let death_cause = match r.u8()? {
+                0 => None,
+                t => Some(from_tag(&DEATH_CAUSES, t - 1).map_err(|_| DecodeError::BadTag(t))?),
+            };
+            // Wall crashes are recorded just off the board, so no bounds check.
+            let crash_site = match r.u8()? {
+                0 => None,
+                1 => Some(r.point()?),
+                t => return Err(DecodeError::BadTag(t)),
+            };
+            let spawn = (
+                r.point()?,
+                from_tag(&DIRECTIONS, r.u8()?)?,
+                r.u32()? as usize,
+            );
+            let mut body = Body::default();
+            for _ in 0..r.u32()? {
+                body.push_back(on_board(r.point()?)?);
+            }
+            if body.len() == 0 {
+                return Err(DecodeError::EmptySnake);
+            }
+            snakes.push(Snake {
+                body,
+                dir,
+                input_queue: VecDeque::new(),
+                pending_growth,
+                score,
+                death_cause,
+                crash_site,
+                spawn,
+            });
+        }
+        if snakes.is_empty() {
+            return Err(DecodeError::EmptySnake);
+        }
+
+        let mut food = BTreeMap::new();
+        let mut food_spawn_ticks = BTreeMap::new();
+        for _ in 0..r.u32()? {
+            let p = on_board(r.point()?)?;
+            food.insert(p, from_tag(&FOOD_KINDS, r.u8()?)?);
+            food_spawn_ticks.insert(p, u64::from_le_bytes(r.take()?));
+        }
+        let mut obstacles = BTreeSet::new();
+        for _ in 0..r.u32()? {
+            obstacles.insert(on_board(r.point()?)?);
+        }
+        if !r.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        // Swap the RNG in afterwards so the fresh game's food spawns don't
+        // advance it.
+        let mut game = Self::with_seed(cfg, 0);
+        game.rng = GameRng::ChaCha(rng);
+        game.snakes = snakes;
+        game.food = food;
+        game.obstacles = obstacles;
+        game.status = status;
+        game.high_score = high_score;
+        game.ticks = ticks;
+        game.food_spawn_ticks = food_spawn_ticks;
+        game.foods_spawned = foods_spawned;
+        game.foods_eaten = foods_eaten;
+        game.ticks_since_food = ticks_since_food;
+        Ok(game)
+    }
+}
+
+fn tag_of<T: PartialEq>(all: &[T], value: T) -> u8 {
+    all.iter()
+        .position(|v| *v == value)
+        .expect("every variant is listed") as u8
+}
+
+fn from_tag<T: Copy>(all: &[T], tag: u8) -> Result<T, DecodeError> {
+    all.get(tag as usize)
+        .copied()
+        .ok_or(DecodeError::BadTag(tag))
+}
+
+fn put_u32(out: &mut Vec<u8>, v: u32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn put_i32(out: &mut Vec<u8>, v: i32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn put_len(out: &mut Vec<u8>, len: usize) -> Result<(), EncodeError> {
+    put_u32(
+        out,
+        u32::try_from(len).map_err(|_| EncodeError::TooLarge(len))?,
+    );
+    Ok(())
+}
+
+fn put_point(out: &mut Vec<u8>, p: Point) {
+    put_i32(out, p.x);
+    put_i32(out, p.y);
+}
+
+/// Consumes the input front to back.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let (head, rest) = self
+            .bytes
+            .split_first_chunk::<N>()
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        self.bytes = rest;
+        Ok(*head)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        self.take::<1>().map(|[b]| b)
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn i32(&mut self) -> Result<i32, DecodeError> {
+        self.take().map(i32::from_le_bytes)
+    }
+
+    fn point(&mut self) -> Result<Point, DecodeError> {
+        Ok(Point::new(self.i32()?, self.i32()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeathStyle, Direction};
+    use alloc::boxed::Box;
+
+    fn played_game() -> GameState {
+        let cfg = GameConfig::builder()
+            .width(12)
+            .height(9)
+            .food_count(3)
+            .build();
+        let mut g = GameState::with_seed(cfg, 7);
+        g.add_snake(Point::new(3, 1), Direction::Right, 3);
+        g.add_obstacle(Point::new(10, 7));
+        for dir in [Direction::Up, Direction::Right, Direction::Right] {
+            g.tick_with(Some(dir));
+        }
+        g
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut g = played_game();
+        let bytes = g.to_bytes().unwrap();
+        let mut restored = GameState::from_bytes(g.config().clone(), &bytes).unwrap();
+        assert_eq!(restored.to_bytes().unwrap(), bytes);
+        assert_eq!(restored.snapshot(), g.snapshot());
+        assert_eq!(restored.rng_word_pos(), g.rng_word_pos());
+
+        // Both copies keep playing, and spawning food, identically.
+        for _ in 0..20 {
+            g.tick_with(Some(Direction::Down));
+            restored.tick_with(Some(Direction::Down));
+        }
+        assert_eq!(restored.to_bytes(), g.to_bytes());
+    }
+
+    #[test]
+    fn crash_site_round_trips() {
+        let cfg = GameConfig::builder()
+            .width(10)
+            .height(8)
+            .death_style(DeathStyle::ShowCrash)
+            .build();
+        let mut g = GameState::with_seed(cfg, 42);
+        g.clear_food();
+        while g.tick().status == GameStatus::Running {}
+        assert_eq!(g.crash_site(), Some(Point::new(10, 4)));
+        let bytes = g.to_bytes().unwrap();
+        let restored = GameState::from_bytes(g.config().clone(), &bytes).unwrap();
+        assert_eq!(restored.crash_site(), g.crash_site());
+    }
+
+    #[test]
+    fn custom_rng_is_an_encode_error() {
+        let g = GameState::with_boxed_rng(
+            GameConfig::default(),
+            Box::new(ChaCha8Rng::seed_from_u64(1)),
+        );
+        assert_eq!(g.to_bytes(), Err(EncodeError::CustomRng));
+    }
+
+    #[test]
+    fn oversized_growth_is_an_encode_error() {
+        let mut g = GameState::with_seed(GameConfig::default(), 1);
+        g.grow(usize::MAX);
+        assert_eq!(g.to_bytes(), Err(EncodeError::TooLarge(usize::MAX)));
+    }
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        let cfg = GameConfig::default();
+        let bytes = played_game().to_bytes().unwrap();
+        for len in 0..bytes.len() {
+            assert_eq!(
+                GameState::from_bytes(cfg.clone(), &bytes[..len]).err(),
+                Some(DecodeError::UnexpectedEnd),
+                "prefix of {len} bytes"
+            );
+        }
+
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert_eq!(
+            GameState::from_bytes(cfg.clone(), &extra).err(),
+            Some(DecodeError::TrailingBytes)
+        );
+
+        let mut version = bytes.clone();
+        version[0] = 9;
+        assert_eq!(
+            GameState::from_bytes(cfg.clone(), &version).err(),
+            Some(DecodeError::UnknownVersion(9))
+        );
+
+        let mut status = bytes.clone();
+        status[9] = 200;
+        assert_eq!(
+            GameState::from_bytes(cfg.clone(), &status).err(),
+            Some(DecodeError::BadTag(200))
+        );
+
+        // Boards too big to be real, whether or not width * height overflows.
+        for (width, height) in [(i32::MAX, i32::MAX), (1 << 13, 1 << 12)] {
+            let mut huge = bytes.clone();
+            huge[1..5].copy_from_slice(&width.to_le_bytes());
+            huge[5..9].copy_from_slice(&height.to_le_bytes());
+            assert_eq!(
+                GameState::from_bytes(cfg.clone(), &huge).err(),
+                Some(DecodeError::BadDimensions { width, height })
+            );
+        }
+
+        // Shrink the board so the snakes no longer fit on it.
+        let mut narrow = bytes;
+        narrow[1..5].copy_from_slice(&2i32.to_le_bytes());
+        assert!(matches!(
+            GameState::from_bytes(cfg, &narrow),
+            Err(DecodeError::OutOfBounds(_))
+        ));
+    }
+}
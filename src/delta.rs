@@ -0,0 +1,401 @@
+//! Small updates between two states of the same game, for a server to
+//! broadcast instead of whole snapshots.
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::{Body, DeathCause, Direction, FoodKind, GameState, GameStatus, Point, Snake};
+
+/// What changed between two states of a game; see [`GameState::delta`].
+///
+/// Counters are carried as their new values rather than differences.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateDelta {
+    /// `ticks` of the state the delta applies to.
+    pub base_ticks: u64,
+    /// One entry per snake, the player first.
+    pub snakes: Vec<SnakeDelta>,
+    /// New or changed food, with the tick it appeared on.
+    pub food_added: Vec<(Point, FoodKind, u64)>,
+    pub food_removed: Vec<Point>,
+    pub obstacles_added: Vec<Point>,
+    pub obstacles_removed: Vec<Point>,
+    pub status: GameStatus,
+    pub ticks: u64,
+    pub high_score: u32,
+    pub rng_word_pos: u128,
+    pub foods_spawned: u32,
+    pub foods_eaten: u32,
+    pub ticks_since_food: u64,
+}
+
+/// How one snake changed. A tick usually adds one head and drops one tail
+/// segment, or none while growing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnakeDelta {
+    /// Body length in the base state; 0 for a snake added since.
+    pub base_len: usize,
+    /// Segments added at the head end, the new head first.
+    pub heads: Vec<Point>,
+    /// Segments dropped from the tail end.
+    pub tail_removed: usize,
+    pub score_delta: i64,
+    pub dir: Direction,
+    pub pending_growth: usize,
+    pub death_cause: Option<DeathCause>,
+    pub crash_site: Option<Point>,
+}
+
+/// Why [`GameState::apply_delta`] refused a delta.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaError {
+    /// The game isn't at the tick the delta was taken from.
+    WrongBase { expected: u64, found: u64 },
+    /// The delta covers fewer snakes than the game has.
+    SnakeCount { expected: usize, found: usize },
+    /// A snake's length differs from the delta's base.
+    LengthMismatch {
+        snake: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// Applying the delta would leave a snake with no segments.
+    EmptySnake(usize),
+    /// A snake loses more tail segments than it has.
+    TailTooLong {
+        snake: usize,
+        removed: usize,
+        len: usize,
+    },
+    /// A new segment, food or obstacle lies off the board.
+    OutOfBounds(Point),
+}
+
+impl fmt::Display for DeltaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongBase { expected, found } => {
+                write!(
+                    f,
+                    "delta applies to tick {expected}, game is at tick {found}"
+                )
+            }
+            Self::SnakeCount { expected, found } => {
+                write!(f, "delta covers {expected} snakes, game has {found}")
+            }
+            Self::LengthMismatch {
+                snake,
+                expected,
+                found,
+            } => write!(
+                f,
+                "snake {snake} should have {expected} segments, has {found}"
+            ),
+            Self::EmptySnake(i) => write!(f, "delta would leave snake {i} empty"),
+            Self::TailTooLong {
+                snake,
+                removed,
+                len,
+            } => write!(
+                f,
+                "delta drops {removed} segments from snake {snake}, which has {len}"
+            ),
+            Self::OutOfBounds(p) => write!(f, "({}, {}) is off the board", p.x, p.y),
+        }
+    }
+}
+
+impl core::error::Error for DeltaError {}
+
+impl GameState {
+    /// What changed since `prev`, an earlier state of this game.
+    ///
+    /// Snakes added since `prev` are sent whole. Applying the result to
+    /// `prev` with [`GameState::apply_delta`] reproduces `self`, apart from
+    /// the parts [`GameState::to_bytes`] leaves out as well.
+    pub fn delta(&self, prev: &GameState) -> StateDelta {
+        let snakes = self
+            .snakes
+            .iter()
+            .enumerate()
+            .map(|(i, snake)| {
+                let new: Vec<Point> = snake.body.iter().copied().collect();
+                let old: Vec<Point> = prev
+                    .snakes
+                    .get(i)
+                    .map(|s| s.body.iter().copied().collect())
+                    .unwrap_or_default();
+                // A tick adds at most one head segment, so only try none or
+                // one before falling back to sending the whole body.
+                let added = (0..=1)
+                    .find(|&k| {
+                        k <= new.len() && {
+                            let kept = new.len() - k;
+                            kept <= old.len() && new[k..] == old[..kept]
+                        }
+                    })
+                    .unwrap_or(new.len());
+                let old_score = prev.snakes.get(i).map_or(0, |s| s.score);
+                SnakeDelta {
+                    base_len: old.len(),
+                    heads: new[..added].to_vec(),
+                    tail_removed: old.len() - (new.len() - added),
+                    score_delta: i64::from(snake.score) - i64::from(old_score),
+                    dir: snake.dir,
+                    pending_growth: snake.pending_growth,
+                    death_cause: snake.death_cause,
+                    crash_site: snake.crash_site,
+                }
+            })
+            .collect();
+
+        StateDelta {
+            base_ticks: prev.ticks,
+            snakes,
+            food_added: self
+                .food
+                .iter()
+                .map(|(&p, &kind)| (p, kind, self.food_spawn_tick(&p)))
+                .filter(|&(p, kind, spawned)| {
+                    prev.food.get(&p) != Some(&kind) || prev.food_spawn_tick(&p) != spawned
+                })
+                .collect(),
+            food_removed: prev
+                .food
+                .keys()
+                .filter(|p| !self.food.contains_key(p))
+                .copied()
+                .collect(),
+            obstacles_added: self
+                .obstacles
+                .difference(&prev.obstacles)
+                .copied()
+                .collect(),
+            obstacles_removed: prev
+                .obstacles
+                .difference(&self.obstacles)
+                .copied()
+                .collect(),
+            status: self.status,
+            ticks: self.ticks,
+            high_score: self.high_score,
+            rng_word_pos: self.rng.word_pos(),
+            foods_spawned: self.foods_spawned,
+            foods_eaten: self.foods_eaten,
+            ticks_since_food: self.ticks_since_food,
+        }
+    }
+
+    /// Bring this game up to date with a [`StateDelta`] from
+    /// [`GameState::delta`].
+    ///
+    /// The game must match the delta's base state: same tick, same snakes
+    /// and snake lengths. Nothing is changed if it doesn't.
+    pub fn apply_delta(&mut self, delta: &StateDelta) -> Result<(), DeltaError> {
+        if self.ticks != delta.base_ticks {
+            return Err(DeltaError::WrongBase {
+                expected: delta.base_ticks,
+                found: self.ticks,
+            });
+        }
+        if delta.snakes.len() < self.snakes.len() {
+            return Err(DeltaError::SnakeCount {
+                expected: delta.snakes.len(),
+                found: self.snakes.len(),
+            });
+        }
+        for (i, d) in delta.snakes.iter().enumerate() {
+            let found = self.snakes.get(i).map_or(0, |s| s.body.len());
+            if found != d.base_len {
+                return Err(DeltaError::LengthMismatch {
+                    snake: i,
+                    expected: d.base_len,
+                    found,
+                });
+            }
+            if d.tail_removed > d.base_len {
+                return Err(DeltaError::TailTooLong {
+                    snake: i,
+                    removed: d.tail_removed,
+                    len: d.base_len,
+                });
+            }
+            if d.heads.is_empty() && d.tail_removed == d.base_len {
+                return Err(DeltaError::EmptySnake(i));
+            }
+        }
+        let mut new_points = delta
+            .snakes
+            .iter()
+            .flat_map(|d| &d.heads)
+            .chain(delta.food_added.iter().map(|(p, _, _)| p))
+            .chain(&delta.obstacles_added);
+        if let Some(&p) = new_points.find(|&&p| self.out_of_bounds(p)) {
+            return Err(DeltaError::OutOfBounds(p));
+        }
+
+        for (i, d) in delta.snakes.iter().enumerate() {
+            if i == self.snakes.len() {
+                let head = d.heads[0];
+                self.snakes.push(Snake {
+                    body: Body::default(),
+                    dir: d.dir,
+                    input_queue: VecDeque::new(),
+                    pending_growth: 0,
+                    score: 0,
+                    death_cause: None,
+                    crash_site: None,
+                    spawn: (head, d.dir, d.heads.len()),
+                });
+            }
+            let snake = &mut self.snakes[i];
+            snake.body.truncate(d.base_len - d.tail_removed);
+            for &p in d.heads.iter().rev() {
+                snake.body.push_front(p);
+            }
+            let score = i64::from(snake.score) + d.score_delta;
+            snake.score = score.clamp(0, i64::from(u32::MAX)) as u32;
+            snake.dir = d.dir;
+            snake.pending_growth = d.pending_growth;
+            snake.death_cause = d.death_cause;
+            snake.crash_site = d.crash_site;
+        }
+
+        for p in &delta.food_removed {
+            self.food.remove(p);
+            self.food_spawn_ticks.remove(p);
+        }
+        for &(p, kind, spawned) in &delta.food_added {
+            self.food.insert(p, kind);
+            self.food_spawn_ticks.insert(p, spawned);
+        }
+        for p in &delta.obstacles_removed {
+            self.obstacles.remove(p);
+        }
+        self.obstacles.extend(delta.obstacles_added.iter().copied());
+        self.status = delta.status;
+        self.ticks = delta.ticks;
+        self.high_score = delta.high_score;
+        self.rng.set_word_pos(delta.rng_word_pos);
+        self.foods_spawned = delta.foods_spawned;
+        self.foods_eaten = delta.foods_eaten;
+        self.ticks_since_food = delta.ticks_since_food;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameConfig;
+    use crate::ai::bfs_policy;
+
+    /// An independent copy of `g`, since `GameState` isn't `Clone`.
+    fn copy(g: &GameState) -> GameState {
+        GameState::from_bytes(g.config().clone(), &g.to_bytes().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn single_tick_delta_reproduces_the_new_state() {
+        let cfg = GameConfig::builder().width(12).height(9).build();
+        let mut g = GameState::with_seed(cfg, 5);
+        g.add_snake(Point::new(3, 1), Direction::Right, 3);
+        // Play until food has been eaten twice, checking every tick.
+        for _ in 0..500 {
+            if g.score() >= 2 {
+                break;
+            }
+            let old = copy(&g);
+            let dir = bfs_policy(&g);
+            g.tick_with(dir);
+            let delta = g.delta(&old);
+            assert!(delta.snakes[0].heads.len() <= 1, "{delta:?}");
+
+            let mut synced = copy(&old);
+            synced.apply_delta(&delta).unwrap();
+            assert_eq!(synced.to_bytes(), g.to_bytes(), "tick {}", g.ticks());
+        }
+        assert!(g.score() >= 2, "food was eaten within 500 ticks");
+    }
+
+    #[test]
+    fn delta_rejects_a_mismatched_base() {
+        let mut g = GameState::with_seed(GameConfig::default(), 5);
+        let old = copy(&g);
+        g.tick();
+        let delta = g.delta(&old);
+
+        // Already up to date: the tick no longer matches.
+        let mut ahead = copy(&g);
+        assert_eq!(
+            ahead.apply_delta(&delta),
+            Err(DeltaError::WrongBase {
+                expected: 0,
+                found: 1
+            })
+        );
+        assert_eq!(ahead.to_bytes(), g.to_bytes());
+
+        let mut grown = copy(&old);
+        grown.player_mut().body.push_back(Point::new(0, 0));
+        assert!(matches!(
+            grown.apply_delta(&delta),
+            Err(DeltaError::LengthMismatch { snake: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn delta_rejects_impossible_changes() {
+        let mut g = GameState::with_seed(GameConfig::default(), 5);
+        let old = copy(&g);
+        g.tick();
+        let delta = g.delta(&old);
+        let len = delta.snakes[0].base_len;
+
+        let mut too_long = delta.clone();
+        too_long.snakes[0].tail_removed = len + 1;
+        let mut off_board = delta.clone();
+        off_board
+            .food_added
+            .push((Point::new(-1, 0), FoodKind::Normal, 1));
+        let mut off_board_head = delta;
+        off_board_head.snakes[0].heads[0] = Point::new(0, 99);
+
+        let mut synced = copy(&old);
+        assert_eq!(
+            synced.apply_delta(&too_long),
+            Err(DeltaError::TailTooLong {
+                snake: 0,
+                removed: len + 1,
+                len
+            })
+        );
+        assert_eq!(
+            synced.apply_delta(&off_board),
+            Err(DeltaError::OutOfBounds(Point::new(-1, 0)))
+        );
+        assert_eq!(
+            synced.apply_delta(&off_board_head),
+            Err(DeltaError::OutOfBounds(Point::new(0, 99)))
+        );
+        assert_eq!(synced.to_bytes(), old.to_bytes(), "nothing changed");
+    }
+
+    #[test]
+    fn big_jumps_send_the_whole_body() {
+        let mut g = GameState::with_seed(GameConfig::default(), 5);
+        let old = copy(&g);
+        for _ in 0..3 {
+            g.tick();
+        }
+        let delta = g.delta(&old);
+        assert_eq!(delta.snakes[0].heads.len(), g.player().body.len());
+        assert_eq!(delta.snakes[0].tail_removed, old.player().body.len());
+        let mut synced = copy(&old);
+        synced.apply_delta(&delta).unwrap();
+        assert_eq!(synced.to_bytes(), g.to_bytes());
+    }
+}
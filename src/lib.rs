@@ -1,12 +1,35 @@
-use rand::{Rng, SeedableRng};
+#![cfg_attr(not(test), no_std)]
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+#[cfg(feature = "serde")]
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use body::Body;
+use core::fmt;
+use core::ops;
+use core::time::Duration;
+use rand::{Rng, RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
-use std::collections::{HashSet, VecDeque};
+use rng::GameRng;
+use spawn::{FoodSpawner, UniformSpawner};
+pub mod ai;
+mod body;
+pub mod bytes;
+pub mod delta;
 pub mod render;
+mod rng;
+pub mod spawn;
+#[cfg(feature = "wasm")]
+pub mod web;
 
 /// Integer coordinate type for grid cells (not pixels)
 pub type Coord = i32;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     pub x: Coord,
     pub y: Coord,
@@ -17,9 +40,41 @@ impl Point {
     pub const fn new(x: Coord, y: Coord) -> Self {
         Self { x, y }
     }
+
+    /// The adjacent cell one step in `dir`, without wrapping.
+    #[inline]
+    pub fn neighbor(self, dir: Direction) -> Self {
+        let (dx, dy) = dir.dx_dy();
+        self + Self::new(dx, dy)
+    }
+
+    /// Taxicab distance, ignoring board wrapping.
+    #[inline]
+    pub fn manhattan(self, other: Self) -> u32 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl ops::Add for Point {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl ops::Sub for Point {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     Up,
     Down,
@@ -40,438 +95,5368 @@ impl Direction {
 
     #[inline]
     pub fn is_opposite(self, other: Self) -> bool {
-        matches!(
-            (self, other),
-            (Self::Up, Self::Down)
-                | (Self::Down, Self::Up)
-                | (Self::Right, Self::Left)
-                | (Self::Left, Self::Right)
-        )
+        self.opposite() == other
+    }
+
+    /// The heading after a 90° counter-clockwise turn (as seen on screen).
+    #[inline]
+    pub fn turn_left(self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Left => Self::Down,
+            Self::Down => Self::Right,
+            Self::Right => Self::Up,
+        }
+    }
+
+    /// The heading after a 90° clockwise turn (as seen on screen).
+    #[inline]
+    pub fn turn_right(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    #[inline]
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    /// The vim movement keys: `h` left, `j` down, `k` up, `l` right.
+    pub fn from_vim_char(c: char) -> Option<Self> {
+        match c {
+            'h' => Some(Self::Left),
+            'j' => Some(Self::Down),
+            'k' => Some(Self::Up),
+            'l' => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    /// Arrow-like glyph for a head facing this way: `^`, `v`, `<` or `>`.
+    pub(crate) fn head_glyph(self) -> char {
+        match self {
+            Self::Up => '^',
+            Self::Down => 'v',
+            Self::Left => '<',
+            Self::Right => '>',
+        }
     }
+
+    /// Inverse of [`Direction::head_glyph`].
+    pub(crate) fn from_head_glyph(ch: char) -> Option<Self> {
+        DIRECTIONS.into_iter().find(|d| d.head_glyph() == ch)
+    }
+}
+
+/// Every heading, in a fixed order.
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+/// A steering input relative to the snake's current heading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Turn {
+    Left,
+    Right,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameStatus {
     Running,
+    /// Frozen by the driver; ticks are no-ops until unpaused.
+    Paused,
     Dead,
+    /// A configured win condition was met; ticks are no-ops.
+    Won,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameConfig {
     pub width: Coord,
     pub height: Coord,
+    /// Old switch for [`WallBehavior::Wrap`]; when true it overrides `wall_behavior`.
+    /// To migrate, replace `wrap_edges: true` with `wall_behavior: WallBehavior::Wrap`.
+    #[deprecated(note = "use `wall_behavior: WallBehavior::Wrap`")]
     pub wrap_edges: bool,
-    /// Initial snake length (>= 1)
+    /// What happens when the head runs into the edge of the board
+    pub wall_behavior: WallBehavior,
+    /// Initial snake length (>= 1). A body that doesn't fit behind the head
+    /// folds back along the next row or column; see [`GameState::add_snake`].
     pub initial_len: usize,
     /// If true, ensure an odd aspect for Braille rasterization later (2x4 cell mapping)
     pub braille_friendly: bool,
+    /// Number of foods kept on the board at once
+    pub food_count: usize,
+    /// Base score awarded for each food eaten
+    pub points_per_food: u32,
+    /// Extra points for eating food soon after it spawns
+    pub food_bonus: Option<FoodBonus>,
+    /// Immovable walls placed on every reset
+    pub obstacles: Vec<Point>,
+    /// Probability (0.0..=1.0) that a spawned food is [`FoodKind::Shrink`]
+    pub shrink_food_chance: f64,
+    /// Tail segments removed by shrink food; the snake never drops below length 1
+    pub shrink_amount: usize,
+    /// Score deducted for eating shrink food
+    pub shrink_penalty: u32,
+    /// Heading at the start of each game; the body trails behind it
+    pub start_dir: Direction,
+    /// Where the head starts, or `None` to center it
+    pub start_pos: Option<Point>,
+    /// The player reaching this score wins the game
+    pub win_score: Option<u32>,
+    /// Total foods ever spawned; eating the last one wins the game
+    pub total_food: Option<u32>,
+    /// Food never spawns closer than this to the head, unless no such cell can be found
+    pub min_food_distance: Coord,
+    /// Direction presses buffered ahead of the snake, one applied per tick (at least 1)
+    pub input_queue_capacity: usize,
+    /// The snake starves after this many consecutive ticks without eating
+    pub max_ticks_without_food: Option<u64>,
+    /// How a fatal wall or obstacle hit is shown once the game freezes
+    pub death_style: DeathStyle,
+    /// Who survives when two snakes' heads collide
+    pub head_to_head: HeadToHeadRule,
+    /// Number of trailing segments the head may pass through without dying (0 = none)
+    pub self_collision_grace: usize,
+    /// Segments added per normal food, unfurling one per tick like [`GameState::grow`]
+    pub grow_per_food: usize,
+    /// Drop a random obstacle on the board every N points (None = never)
+    pub obstacles_per_score: Option<u32>,
+    /// How front-ends should speed up ticks as the score rises; see [`SpeedCurve::interval`]
+    pub speed_curve: SpeedCurve,
+    /// Win once the player's length covers this fraction of the board (None = never)
+    pub fill_to_win: Option<f32>,
+    /// Chance (0.0..=1.0) that each free cell gets an obstacle on reset, for
+    /// a random maze. The snakes and the cells just ahead of their heads stay
+    /// clear, pockets the player couldn't reach are filled in, and at least
+    /// one reachable cell is left for food.
+    pub maze_density: f32,
+}
+
+/// What the board shows after the snake hits a wall or obstacle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeathStyle {
+    /// Keep the last pre-crash frame.
+    #[default]
+    Freeze,
+    /// Mark the cell the head crashed into (for walls, the edge cell it
+    /// left the board from) with [`CellKind::Crash`].
+    ShowCrash,
+}
+
+/// What happens when a head runs into the edge of the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WallBehavior {
+    /// The snake dies with [`DeathCause::Wall`].
+    #[default]
+    Die,
+    /// Practice mode: the snake stays put for the tick and the game goes on.
+    Stop,
+    /// The head comes out on the opposite edge.
+    Wrap,
+}
+
+/// Outcome when two snakes' heads meet in one cell or pass through each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HeadToHeadRule {
+    /// Both snakes die.
+    #[default]
+    BothDie,
+    /// The longer snake survives; equal lengths both die. A survivor that
+    /// was moving onto the loser's head stays put for the tick.
+    LongerSurvives,
+}
+
+/// What eating a piece of food does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FoodKind {
+    /// Grows the snake and scores points.
+    #[default]
+    Normal,
+    /// Removes tail segments and optionally costs points.
+    Shrink,
+}
+
+/// Time-decaying bonus added to [`GameConfig::points_per_food`].
+///
+/// Eating a food `n` ticks after it appeared awards
+/// `max.saturating_sub(n / decay_ticks)` extra points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FoodBonus {
+    pub max: u32,
+    /// Ticks per point of decay (>= 1)
+    pub decay_ticks: u64,
 }
 
 impl Default for GameConfig {
+    // Spells out the deprecated `wrap_edges` alias.
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             width: 40,
             height: 24,
             wrap_edges: false,
+            wall_behavior: WallBehavior::Die,
             initial_len: 4,
             braille_friendly: true,
+            food_count: 1,
+            points_per_food: 1,
+            food_bonus: None,
+            obstacles: Vec::new(),
+            shrink_food_chance: 0.0,
+            shrink_amount: 2,
+            shrink_penalty: 0,
+            start_dir: Direction::Right,
+            start_pos: None,
+            win_score: None,
+            total_food: None,
+            min_food_distance: 0,
+            input_queue_capacity: 3,
+            max_ticks_without_food: None,
+            death_style: DeathStyle::Freeze,
+            head_to_head: HeadToHeadRule::BothDie,
+            self_collision_grace: 0,
+            grow_per_food: 1,
+            obstacles_per_score: None,
+            speed_curve: SpeedCurve::Linear,
+            fill_to_win: None,
+            maze_density: 0.0,
         }
     }
 }
 
-/// UI-agnostic result of a tick.
+impl GameConfig {
+    /// Start from [`GameConfig::default`] and override only the fields you care about.
+    pub fn builder() -> GameConfigBuilder {
+        GameConfigBuilder::default()
+    }
+
+    /// The wall behavior in effect: [`WallBehavior::Wrap`] if `wrap_edges`
+    /// is set, otherwise `wall_behavior`.
+    pub fn walls(&self) -> WallBehavior {
+        #[allow(deprecated)]
+        if self.wrap_edges {
+            WallBehavior::Wrap
+        } else {
+            self.wall_behavior
+        }
+    }
+
+    /// Manhattan distance between `a` and `b`, taking the shorter way around
+    /// each axis when edges wrap.
+    pub fn wrapped_distance(&self, a: Point, b: Point) -> u32 {
+        let dx = a.x.abs_diff(b.x);
+        let dy = a.y.abs_diff(b.y);
+        if self.walls() != WallBehavior::Wrap {
+            return dx + dy;
+        }
+        let dx = dx.min((self.width as u32).saturating_sub(dx));
+        let dy = dy.min((self.height as u32).saturating_sub(dy));
+        dx + dy
+    }
+
+    /// Reject boards that can't host a game.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.width <= 0 {
+            return Err(ConfigError::NonPositiveWidth(self.width));
+        }
+        if self.height <= 0 {
+            return Err(ConfigError::NonPositiveHeight(self.height));
+        }
+        if self.initial_len == 0 {
+            return Err(ConfigError::ZeroInitialLen);
+        }
+        let max = self.width as usize * self.height as usize;
+        if self.initial_len > max {
+            return Err(ConfigError::InitialLenTooLarge {
+                len: self.initial_len,
+                max,
+            });
+        }
+        if let Some(p) = self.start_pos
+            && (p.x < 0 || p.x >= self.width || p.y < 0 || p.y >= self.height)
+        {
+            return Err(ConfigError::StartOutOfBounds(p));
+        }
+        if let Some(fraction) = self.fill_to_win
+            && !(fraction > 0.0 && fraction <= 1.0)
+        {
+            return Err(ConfigError::FillToWinOutOfRange);
+        }
+        if !(0.0..=1.0).contains(&self.maze_density) {
+            return Err(ConfigError::MazeDensityOutOfRange);
+        }
+        Ok(())
+    }
+}
+
+/// Reasons a [`GameConfig`] describes an impossible board.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct TickResult {
-    pub ate_food: bool,
-    pub status: GameStatus,
-    pub score: u32,
+pub enum ConfigError {
+    NonPositiveWidth(Coord),
+    NonPositiveHeight(Coord),
+    /// The starting snake has no segments.
+    ZeroInitialLen,
+    /// The starting snake doesn't fit on the board.
+    InitialLenTooLarge {
+        len: usize,
+        max: usize,
+    },
+    /// `start_pos` isn't on the board.
+    StartOutOfBounds(Point),
+    /// `fill_to_win` is NaN or outside `(0.0, 1.0]`.
+    FillToWinOutOfRange,
+    /// `maze_density` is NaN or outside `0.0..=1.0`.
+    MazeDensityOutOfRange,
+    /// A starting snake would cover this cell outside the playable mask; see
+    /// [`GameState::set_playable`].
+    StartOffMask(Point),
 }
 
-#[derive(Debug)]
-pub struct GameState {
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonPositiveWidth(w) => write!(f, "board width must be positive, got {w}"),
+            Self::NonPositiveHeight(h) => write!(f, "board height must be positive, got {h}"),
+            Self::ZeroInitialLen => f.write_str("initial snake length must be at least 1"),
+            Self::InitialLenTooLarge { len, max } => {
+                write!(f, "initial snake length {len} exceeds board capacity {max}")
+            }
+            Self::StartOutOfBounds(p) => {
+                write!(f, "start position ({}, {}) is off the board", p.x, p.y)
+            }
+            Self::FillToWinOutOfRange => f.write_str("fill_to_win must be above 0 and at most 1"),
+            Self::MazeDensityOutOfRange => f.write_str("maze_density must be between 0 and 1"),
+            Self::StartOffMask(p) => write!(
+                f,
+                "starting snake covers ({}, {}), outside the playable mask",
+                p.x, p.y
+            ),
+        }
+    }
+}
+
+impl core::error::Error for ConfigError {}
+
+/// Why [`GameState::from_ascii`] rejected a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsciiBoardError {
+    /// The board has no rows.
+    Empty,
+    /// A row's length differs from the first row's.
+    RaggedRow {
+        row: Coord,
+        len: Coord,
+        expected: Coord,
+    },
+    /// A character other than `O o * # .`.
+    UnknownCell { at: Point, ch: char },
+    /// There is no head (`O` or `^v<>`).
+    MissingHead,
+    /// There is more than one head; carries the second.
+    ExtraHead(Point),
+    /// A segment touches more than one unclaimed `o`, so the order is unclear.
+    AmbiguousBody(Point),
+    /// An `o` that isn't part of the chain starting at the head.
+    DetachedBody(Point),
+}
+
+impl fmt::Display for AsciiBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "board has no rows"),
+            Self::RaggedRow { row, len, expected } => {
+                write!(f, "row {row} is {len} cells wide, expected {expected}")
+            }
+            Self::UnknownCell { at, ch } => {
+                write!(f, "unknown cell {ch:?} at ({}, {})", at.x, at.y)
+            }
+            Self::MissingHead => write!(f, "board has no head ('O' or one of '^v<>')"),
+            Self::ExtraHead(p) => write!(f, "second head at ({}, {})", p.x, p.y),
+            Self::AmbiguousBody(p) => {
+                write!(
+                    f,
+                    "segment at ({}, {}) touches more than one next segment",
+                    p.x, p.y
+                )
+            }
+            Self::DetachedBody(p) => {
+                write!(
+                    f,
+                    "body segment at ({}, {}) is not connected to the head",
+                    p.x, p.y
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for AsciiBoardError {}
+
+/// Chainable construction of a [`GameConfig`]; unset fields keep their defaults.
+#[derive(Debug, Clone, Default)]
+pub struct GameConfigBuilder {
     cfg: GameConfig,
-    snake: VecDeque<Point>,
-    dir: Direction,
-    /// Applied at the start of the next tick if it's not a 180* turn.
-    pending_dir: Option<Direction>,
-    food: HashSet<Point>, // Supports multiple foods on the board
-    rng: ChaCha8Rng,
-    status: GameStatus,
-    score: u32,
 }
 
-impl GameState {
-    pub fn with_seed(cfg: GameConfig, seed: u64) -> Self {
-        Self::with_rng(cfg, ChaCha8Rng::seed_from_u64(seed))
+impl GameConfigBuilder {
+    pub fn width(mut self, width: Coord) -> Self {
+        self.cfg.width = width;
+        self
     }
 
-    /// Create a new game with deterministic RNG from `seed`.
-    pub fn with_rng(cfg: GameConfig, rng: ChaCha8Rng) -> Self {
-        let mut game = Self {
-            cfg,
-            snake: VecDeque::new(),
-            dir: Direction::Right,
-            pending_dir: None,
-            food: HashSet::new(),
-            rng: rng,
-            status: GameStatus::Running,
-            score: 0,
-        };
-        game.reset();
-        game
+    pub fn height(mut self, height: Coord) -> Self {
+        self.cfg.height = height;
+        self
     }
 
-    /// Create a new game with non-deterministic seed
-    pub fn new(cfg: GameConfig) -> Self {
-        Self::with_rng(cfg, ChaCha8Rng::from_os_rng())
+    #[deprecated(note = "use `.wall_behavior(WallBehavior::Wrap)`")]
+    pub fn wrap_edges(mut self, wrap_edges: bool) -> Self {
+        #[allow(deprecated)]
+        {
+            self.cfg.wrap_edges = wrap_edges;
+        }
+        self
     }
 
-    pub fn config(&self) -> &GameConfig {
-        &self.cfg
+    pub fn wall_behavior(mut self, wall_behavior: WallBehavior) -> Self {
+        self.cfg.wall_behavior = wall_behavior;
+        self
     }
 
-    pub fn status(&self) -> GameStatus {
-        self.status
+    pub fn initial_len(mut self, initial_len: usize) -> Self {
+        self.cfg.initial_len = initial_len;
+        self
     }
 
-    pub fn score(&self) -> u32 {
-        self.score
+    pub fn braille_friendly(mut self, braille_friendly: bool) -> Self {
+        self.cfg.braille_friendly = braille_friendly;
+        self
     }
 
-    pub fn snake_segments(&self) -> impl Iterator<Item = &Point> {
-        self.snake.iter()
+    pub fn food_count(mut self, food_count: usize) -> Self {
+        self.cfg.food_count = food_count;
+        self
     }
 
-    pub fn food_positions(&self) -> impl Iterator<Item = &Point> {
-        self.food.iter()
+    pub fn points_per_food(mut self, points_per_food: u32) -> Self {
+        self.cfg.points_per_food = points_per_food;
+        self
     }
 
-    pub fn head(&self) -> Point {
-        *self.snake.front().expect("snake is non-empty")
+    pub fn food_bonus(mut self, food_bonus: Option<FoodBonus>) -> Self {
+        self.cfg.food_bonus = food_bonus;
+        self
     }
 
-    /// Request a direction change, applied on the next tick if valid.
-    /// (Prevents instantaneous 180° reversal.)
-    pub fn queue_direction(&mut self, dir: Direction) {
-        self.pending_dir = Some(dir);
+    pub fn obstacles(mut self, obstacles: Vec<Point>) -> Self {
+        self.cfg.obstacles = obstacles;
+        self
     }
 
-    /// Resets snake, direction, food, status, and score.
-    pub fn reset(&mut self) {
-        self.status = GameStatus::Running;
-        self.score = 0;
-        self.snake.clear();
-        self.food.clear();
-        self.dir = Direction::Right;
-        self.pending_dir = None;
+    pub fn shrink_food_chance(mut self, shrink_food_chance: f64) -> Self {
+        self.cfg.shrink_food_chance = shrink_food_chance;
+        self
+    }
 
-        // Center the snake horizontally, start heading right.
-        let cx = self.cfg.width / 2;
-        let cy = self.cfg.height / 2;
+    pub fn shrink_amount(mut self, shrink_amount: usize) -> Self {
+        self.cfg.shrink_amount = shrink_amount;
+        self
+    }
 
-        let init_len = self.cfg.initial_len.max(1);
-        for i in 0..init_len as i32 {
-            self.snake.push_back(Point::new(cx - i, cy));
-        }
+    pub fn shrink_penalty(mut self, shrink_penalty: u32) -> Self {
+        self.cfg.shrink_penalty = shrink_penalty;
+        self
+    }
 
-        // Spawn one piece of food for now.
-        self.spawn_food();
+    pub fn start_dir(mut self, start_dir: Direction) -> Self {
+        self.cfg.start_dir = start_dir;
+        self
     }
 
-    /// Advance the game by one tick.
-    pub fn tick(&mut self) -> TickResult {
-        if self.status == GameStatus::Dead {
-            return TickResult {
-                ate_food: false,
-                status: self.status,
-                score: self.score,
-            };
-        }
+    pub fn start_pos(mut self, start_pos: Option<Point>) -> Self {
+        self.cfg.start_pos = start_pos;
+        self
+    }
 
-        // Apply pending direction (if not 180*)
-        if let Some(next) = self.pending_dir.take() {
-            if !next.is_opposite(self.dir) {
-                self.dir = next;
-            }
-        }
+    pub fn win_score(mut self, win_score: Option<u32>) -> Self {
+        self.cfg.win_score = win_score;
+        self
+    }
 
-        let next_head = self.next_head_position();
+    pub fn total_food(mut self, total_food: Option<u32>) -> Self {
+        self.cfg.total_food = total_food;
+        self
+    }
 
-        if !self.cfg.wrap_edges && self.out_of_bounds(next_head) {
-            self.status = GameStatus::Dead;
-            return TickResult {
-                ate_food: false,
-                status: self.status,
-                score: self.score,
-            };
-        }
+    pub fn min_food_distance(mut self, min_food_distance: Coord) -> Self {
+        self.cfg.min_food_distance = min_food_distance;
+        self
+    }
 
-        let next_head = if self.cfg.wrap_edges {
-            self.wrap(next_head)
-        } else {
-            next_head
-        };
+    pub fn input_queue_capacity(mut self, input_queue_capacity: usize) -> Self {
+        self.cfg.input_queue_capacity = input_queue_capacity;
+        self
+    }
 
-        // Self collision: allow moving onto the tail if it will move off (unless eating)
-        let is_eating = self.food.contains(&next_head);
-        let tail_will_move_off = !is_eating;
-        if self.collides_with_body(next_head, tail_will_move_off) {
-            self.status = GameStatus::Dead;
-            return TickResult {
-                ate_food: false,
-                status: self.status,
-                score: self.score,
-            };
-        }
+    pub fn max_ticks_without_food(mut self, max_ticks_without_food: Option<u64>) -> Self {
+        self.cfg.max_ticks_without_food = max_ticks_without_food;
+        self
+    }
 
-        // Move head
-        self.snake.push_front(next_head);
+    pub fn death_style(mut self, death_style: DeathStyle) -> Self {
+        self.cfg.death_style = death_style;
+        self
+    }
 
-        let ate_food = if is_eating {
-            self.food.remove(&next_head);
-            self.score += 1;
-            self.spawn_food();
-            true
-        } else {
-            self.snake.pop_back();
-            false
-        };
+    pub fn head_to_head(mut self, head_to_head: HeadToHeadRule) -> Self {
+        self.cfg.head_to_head = head_to_head;
+        self
+    }
 
-        TickResult {
-            ate_food,
-            status: self.status,
-            score: self.score,
-        }
+    pub fn self_collision_grace(mut self, self_collision_grace: usize) -> Self {
+        self.cfg.self_collision_grace = self_collision_grace;
+        self
     }
 
-    fn next_head_position(&self) -> Point {
-        let (dx, dy) = self.dir.dx_dy();
-        let h = self.head();
-        Point::new(h.x + dx, h.y + dy)
+    pub fn grow_per_food(mut self, grow_per_food: usize) -> Self {
+        self.cfg.grow_per_food = grow_per_food;
+        self
     }
 
-    fn out_of_bounds(&self, p: Point) -> bool {
-        p.x < 0 || p.x >= self.cfg.width || p.y < 0 || p.y >= self.cfg.height
+    pub fn obstacles_per_score(mut self, obstacles_per_score: Option<u32>) -> Self {
+        self.cfg.obstacles_per_score = obstacles_per_score;
+        self
     }
 
-    fn wrap(&self, p: Point) -> Point {
-        let mut x = p.x;
-        let mut y = p.y;
-        if x < 0 {
-            x = self.cfg.width - 1;
-        } else if x >= self.cfg.width {
-            x = 0;
-        }
-        if y < 0 {
-            y = self.cfg.height - 1;
-        } else if y >= self.cfg.height {
-            y = 0;
-        }
-        Point::new(x, y)
+    pub fn speed_curve(mut self, speed_curve: SpeedCurve) -> Self {
+        self.cfg.speed_curve = speed_curve;
+        self
     }
 
-    fn collides_with_body(&self, p: Point, tail_will_move_off: bool) -> bool {
-        // If tail will move, ignore the last segment during collision check.
-        if tail_will_move_off && !self.snake.is_empty() {
-            self.snake
-                .iter()
-                .take(self.snake.len() - 1)
-                .any(|&s| s == p)
-        } else {
-            self.snake.iter().any(|&s| s == p)
-        }
+    pub fn fill_to_win(mut self, fill_to_win: Option<f32>) -> Self {
+        self.cfg.fill_to_win = fill_to_win;
+        self
     }
 
-    fn spawn_food(&mut self) {
-        // Very small grids could be full--avoid inifint loops.
-        let max_attempts = (self.cfg.width as usize)
-            .saturating_mul(self.cfg.height as usize)
-            .saturating_mul(2)
-            .max(8);
-        let snake_set: HashSet<Point> = self.snake.iter().copied().collect();
+    pub fn maze_density(mut self, maze_density: f32) -> Self {
+        self.cfg.maze_density = maze_density;
+        self
+    }
 
-        for _ in 0..max_attempts {
-            let x = self.rng.random_range(0..self.cfg.width) as Coord;
-            let y = self.rng.random_range(0..self.cfg.height) as Coord;
-            let p = Point::new(x, y);
-            if !snake_set.contains(&p) && !self.food.contains(&p) {
-                self.food.insert(p);
-                return;
-            }
-        }
-        // If we fail to find a spot, do nothing (grid is effectively full).
+    pub fn build(self) -> GameConfig {
+        self.cfg
     }
 }
 
-/// A lightweight "raster" to help the renderer later.
-/// Not used by the core tick logic, but makes it trivial to convert to Braille.
-#[derive(Debug, Clone)]
-pub struct Raster2D {
-    pub width: Coord,
-    pub height: Coord,
-    pub cells: Vec<bool>,
+/// UI-agnostic result of a tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickResult {
+    pub ate_food: bool,
+    pub status: GameStatus,
+    pub score: u32,
 }
 
-impl Raster2D {
-    pub fn new(width: Coord, height: Coord) -> Self {
-        let size = (width.max(0) * height.max(0)) as usize;
-        Self {
-            width,
-            height,
-            cells: vec![false; size],
-        }
-    }
+/// Delay between ticks at `score`: one millisecond faster per point, never below `min_millis`.
+///
+/// Same as [`SpeedCurve::Linear`]; see [`SpeedCurve::interval`] for the others.
+pub fn tick_interval(score: u32, base_millis: u64, min_millis: u64) -> Duration {
+    SpeedCurve::Linear.interval(score, base_millis, min_millis)
+}
 
-    #[inline]
-    fn idx(&self, x: Coord, y: Coord) -> Option<usize> {
-        if x < 0 || y < 0 || x > self.width || y > self.height {
-            None
-        } else {
-            Some((y * self.width + x) as usize)
-        }
-    }
+/// How the delay between ticks shrinks as the score rises.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpeedCurve {
+    /// One millisecond faster per point.
+    #[default]
+    Linear,
+    /// About 5% faster per point: a quick ramp that levels off.
+    Exponential,
+    /// Ten milliseconds faster every five points.
+    Stepped,
+}
 
-    pub fn set(&mut self, x: Coord, y: Coord, on: bool) {
-        if let Some(i) = self.idx(x, y) {
-            self.cells[i] = on;
-        }
+impl SpeedCurve {
+    /// Delay between ticks at `score`, starting from `base_millis` and never
+    /// below `min_millis`. Never increases as the score rises.
+    pub fn interval(self, score: u32, base_millis: u64, min_millis: u64) -> Duration {
+        let millis = match self {
+            Self::Linear => base_millis.saturating_sub(score as u64),
+            Self::Exponential => {
+                let mut millis = base_millis;
+                for _ in 0..score {
+                    if millis <= min_millis {
+                        break;
+                    }
+                    millis -= (millis / 20).max(1);
+                }
+                millis
+            }
+            Self::Stepped => base_millis.saturating_sub(score as u64 / 5 * 10),
+        };
+        Duration::from_millis(millis.max(min_millis))
     }
+}
 
-    pub fn get(&self, x: Coord, y: Coord) -> bool {
-        match self.idx(x, y) {
-            Some(idx) => self.cells[idx],
-            None => false,
-        }
-    }
+/// What killed the snake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeathCause {
+    Wall,
+    SelfCollision,
+    Obstacle,
+    /// Went longer than [`GameConfig::max_ticks_without_food`] without eating.
+    Starvation,
+    /// Ran into another snake's body.
+    OtherSnake,
+    /// Moved into the same cell as another snake's head, or swapped cells with it.
+    HeadOn,
 }
 
-pub fn rasterize_game(state: &GameState) -> Raster2D {
-    let mut r = Raster2D::new(state.cfg.width, state.cfg.height);
-    // Draw snake
-    for p in state.snake_segments() {
-        r.set(p.x, p.y, true);
-    }
-    for p in state.food_positions() {
-        r.set(p.x, p.y, true);
-    }
-    r
+/// Something that happened during a tick, in the order it happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    Moved {
+        from: Point,
+        to: Point,
+    },
+    Ate {
+        at: Point,
+    },
+    /// The tail stayed put this tick, lengthening the snake by one.
+    Grew,
+    Died {
+        cause: DeathCause,
+    },
+}
+
+/// Owned copy of the state a renderer typically needs for one frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameSnapshot {
+    pub width: Coord,
+    pub height: Coord,
+    pub head: Point,
+    pub snake_len: usize,
+    pub score: u32,
+    pub status: GameStatus,
+    pub food: Vec<Point>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameState {
+    cfg: GameConfig,
+    /// Every snake on the board; index 0 is the player.
+    snakes: Vec<Snake>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_pairs"))]
+    food: BTreeMap<Point, FoodKind>, // Supports multiple foods on the board
+    obstacles: BTreeSet<Point>,
+    /// Entering a key cell moves the head to its partner cell.
+    #[cfg_attr(feature = "serde", serde(with = "serde_pairs"))]
+    portals: BTreeMap<Point, Point>,
+    /// Cells the snake may occupy, if the board isn't a full rectangle.
+    playable: Option<BTreeSet<Point>>,
+    /// Scripted food positions, used before the spawner while any remain.
+    food_queue: VecDeque<Point>,
+    rng: GameRng,
+    status: GameStatus,
+    /// Best score seen by this game; survives resets.
+    high_score: u32,
+    /// Number of ticks that advanced the snake since the last reset.
+    ticks: u64,
+    /// Value of `ticks` when each food appeared, for the time bonus. Food
+    /// added without a record counts from tick 0.
+    #[cfg_attr(feature = "serde", serde(with = "serde_pairs"))]
+    food_spawn_ticks: BTreeMap<Point, u64>,
+    /// Foods spawned and eaten since the last reset, for `total_food`.
+    foods_spawned: u32,
+    foods_eaten: u32,
+    /// Ticks since food was last eaten, for `max_ticks_without_food`.
+    ticks_since_food: u64,
+    /// Events emitted by the current tick; reused across ticks.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: Vec<GameEvent>,
+    /// Most recent pre-tick states, newest last. Empty unless history is enabled.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history: VecDeque<HistoryEntry>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history_capacity: usize,
+    /// When set, every `queue_direction` call is appended to `input_log`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    recording: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    input_log: Vec<(u64, Direction)>,
+    /// When set, food creeps one cell toward the head every tick.
+    food_drift: bool,
+    /// Picks the cell for each new food.
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_spawner"))]
+    food_spawner: Box<dyn FoodSpawner>,
+    /// Called with each event as `tick` emits it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    on_event: Option<EventCallback>,
+}
+
+/// Boxed [`GameState::set_on_event`] closure, wrapped so `GameState` stays `Debug`.
+struct EventCallback(Box<dyn FnMut(&GameEvent)>);
+
+impl fmt::Debug for EventCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EventCallback")
+    }
+}
+
+/// One snake on the board. Snake 0 is the player steered by
+/// [`GameState::queue_direction`]; others are added with [`GameState::add_snake`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snake {
+    body: Body,
+    dir: Direction,
+    /// Queued presses, oldest first. One is taken per tick and applied if it's not a 180* turn.
+    input_queue: VecDeque<Direction>,
+    /// Segments still to be added; each skips one tail pop.
+    pending_growth: usize,
+    score: u32,
+    death_cause: Option<DeathCause>,
+    /// Cell the head tried to enter on a fatal wall/obstacle hit, under [`DeathStyle::ShowCrash`].
+    crash_site: Option<Point>,
+    /// Head, heading and length to lay the snake out again on reset.
+    spawn: (Point, Direction, usize),
+}
+
+impl Snake {
+    /// A `len`-segment snake with its head at `head`, trailing opposite `dir`.
+    ///
+    /// When the trail reaches the edge of `cfg`'s board it steps sideways,
+    /// towards whichever side has more room, and doubles back. Segments that
+    /// still don't fit become pending growth, so the body never leaves the
+    /// board or overlaps itself.
+    fn new(head: Point, dir: Direction, len: usize, cfg: &GameConfig) -> Self {
+        let (w, h) = (cfg.width, cfg.height);
+        let fits = |p: Point| p.x >= 0 && p.x < w && p.y >= 0 && p.y < h;
+        let room = |d: Direction| match d {
+            Direction::Up => head.y,
+            Direction::Down => h - 1 - head.y,
+            Direction::Left => head.x,
+            Direction::Right => w - 1 - head.x,
+        };
+        let mut trail = dir.opposite();
+        let side = if room(trail.turn_left()) >= room(trail.turn_right()) {
+            trail.turn_left()
+        } else {
+            trail.turn_right()
+        };
+
+        let len = len.max(1);
+        let mut body = Body::from([head]);
+        let mut last = head;
+        while body.len() < len {
+            let straight = last.neighbor(trail);
+            let next = if fits(straight) && !body.contains(&straight) {
+                straight
+            } else {
+                let turn = last.neighbor(side);
+                if !fits(turn) || body.contains(&turn) {
+                    break;
+                }
+                trail = trail.opposite();
+                turn
+            };
+            body.push_back(next);
+            last = next;
+        }
+
+        Self {
+            pending_growth: len - body.len(),
+            body,
+            dir,
+            input_queue: VecDeque::new(),
+            score: 0,
+            death_cause: None,
+            crash_site: None,
+            spawn: (head, dir, len),
+        }
+    }
+
+    fn respawn(&mut self, cfg: &GameConfig) {
+        let (head, dir, len) = self.spawn;
+        *self = Self::new(head, dir, len, cfg);
+    }
+
+    /// Segments from head to tail.
+    pub fn segments(&self) -> impl Iterator<Item = &Point> {
+        self.body.iter()
+    }
+
+    pub fn head(&self) -> Point {
+        *self.body.front().expect("snake is non-empty")
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.dir
+    }
+
+    /// Points this snake has earned from its own meals.
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.death_cause.is_none()
+    }
+
+    /// Why this snake died, or `None` while it's alive.
+    pub fn death_cause(&self) -> Option<DeathCause> {
+        self.death_cause
+    }
+
+    /// Queue a press, dropping repeats and replacing the newest entry when full.
+    fn queue(&mut self, dir: Direction, capacity: usize) {
+        if self.input_queue.back() == Some(&dir) {
+            return;
+        }
+        if self.input_queue.len() >= capacity.max(1) {
+            self.input_queue.pop_back();
+        }
+        self.input_queue.push_back(dir);
+    }
+}
+
+#[cfg(feature = "serde")]
+fn default_spawner() -> Box<dyn FoodSpawner> {
+    Box::new(UniformSpawner)
+}
+
+/// Everything a tick can change, captured so [`GameState::rewind`] can undo it.
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    snakes: Vec<Snake>,
+    food: BTreeMap<Point, FoodKind>,
+    obstacles: BTreeSet<Point>,
+    food_queue: VecDeque<Point>,
+    rng_word_pos: u128,
+    status: GameStatus,
+    high_score: u32,
+    ticks: u64,
+    food_spawn_ticks: BTreeMap<Point, u64>,
+    foods_spawned: u32,
+    foods_eaten: u32,
+    ticks_since_food: u64,
+}
+
+impl GameState {
+    pub fn with_seed(cfg: GameConfig, seed: u64) -> Self {
+        Self::with_rng(cfg, ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Create a new game with deterministic RNG from `seed`.
+    ///
+    /// `ChaCha8Rng` output is the same on every platform, so a seed (plus
+    /// [`GameState::rng_word_pos`]) always reproduces the same food.
+    pub fn with_rng(cfg: GameConfig, rng: ChaCha8Rng) -> Self {
+        Self::with_game_rng(cfg, GameRng::ChaCha(rng))
+    }
+
+    /// Create a new game drawing food and obstacle positions from any RNG,
+    /// e.g. a faster or platform-specific one.
+    ///
+    /// Food is exactly as deterministic as `rng`. Since the game can't see
+    /// inside it, [`GameState::rng_word_pos`] is always 0, [`GameState::rewind`]
+    /// doesn't rewind it, and the game can't be serialized.
+    pub fn with_boxed_rng(cfg: GameConfig, rng: Box<dyn RngCore>) -> Self {
+        Self::with_game_rng(cfg, GameRng::Custom(rng))
+    }
+
+    fn with_game_rng(cfg: GameConfig, rng: GameRng) -> Self {
+        let mut game = Self {
+            cfg,
+            snakes: Vec::new(),
+            food: BTreeMap::new(),
+            obstacles: BTreeSet::new(),
+            portals: BTreeMap::new(),
+            playable: None,
+            food_queue: VecDeque::new(),
+            rng,
+            status: GameStatus::Running,
+            high_score: 0,
+            ticks: 0,
+            food_spawn_ticks: BTreeMap::new(),
+            foods_spawned: 0,
+            foods_eaten: 0,
+            ticks_since_food: 0,
+            events: Vec::new(),
+            history: VecDeque::new(),
+            history_capacity: 0,
+            recording: false,
+            input_log: Vec::new(),
+            food_drift: false,
+            food_spawner: Box::new(UniformSpawner),
+            on_event: None,
+        };
+        game.reset();
+        game
+    }
+
+    /// Like [`GameState::with_seed`], but logs every queued direction so the
+    /// session can be reproduced with [`GameState::replay`].
+    pub fn with_recording(cfg: GameConfig, seed: u64) -> Self {
+        let mut game = Self::with_seed(cfg, seed);
+        game.recording = true;
+        game
+    }
+
+    /// Rebuild a recorded session from its seed, input log and length.
+    ///
+    /// `ticks` is the recorded game's [`GameState::ticks`] when it stopped,
+    /// so ticks after the last input, up to and including the one that ended
+    /// the game, are played too. Returns the rebuilt game and the result of
+    /// every tick.
+    pub fn replay(
+        cfg: GameConfig,
+        seed: u64,
+        log: &[(u64, Direction)],
+        ticks: u64,
+    ) -> (Self, Vec<TickResult>) {
+        let mut game = Self::with_seed(cfg, seed);
+        let mut inputs = log.iter().peekable();
+        let mut results = Vec::new();
+        while game.ticks < ticks {
+            while let Some(&(_, dir)) = inputs.next_if(|&&(t, _)| t == game.ticks) {
+                game.queue_direction(dir);
+            }
+            let res = game.tick();
+            results.push(res);
+            if res.status != GameStatus::Running {
+                break;
+            }
+        }
+        (game, results)
+    }
+
+    /// Play `moves` on a fresh game seeded with `seed`, one tick per move,
+    /// e.g. to pin down behavior in a test or reproduce a bug report. Stops
+    /// early once the game is over. Returns the final game and every tick's result.
+    pub fn scripted(cfg: GameConfig, seed: u64, moves: &[Direction]) -> (Self, Vec<TickResult>) {
+        let mut game = Self::with_seed(cfg, seed);
+        let mut results = Vec::with_capacity(moves.len());
+        for &dir in moves {
+            let res = game.tick_with(Some(dir));
+            results.push(res);
+            if res.status != GameStatus::Running {
+                break;
+            }
+        }
+        (game, results)
+    }
+
+    /// The next `n` food positions a game seeded with `seed` would spawn while
+    /// the snake stays at `snake` (head first).
+    ///
+    /// Each food is treated as eaten before the next one spawns, so every
+    /// position is drawn from the same free cells. Obstacles from
+    /// [`GameConfig::maze_density`] are scattered as the real game would, so
+    /// they're avoided too. Stops early if the board has no room left.
+    pub fn preview_food_sequence(
+        cfg: &GameConfig,
+        seed: u64,
+        snake: &[Point],
+        n: usize,
+    ) -> Vec<Point> {
+        let mut game = Self::with_seed(cfg.clone(), seed);
+        // Redo the maze from a fresh RNG, leaving it where reset left it
+        // before the first food.
+        game.rng = GameRng::ChaCha(ChaCha8Rng::seed_from_u64(seed));
+        game.food.clear();
+        game.obstacles = cfg.obstacles.iter().copied().collect();
+        game.seed_maze();
+        game.player_mut().body = snake.iter().copied().collect();
+
+        let mut preview = Vec::with_capacity(n);
+        while preview.len() < n && game.spawn_food() {
+            let (p, _) = game.food.pop_first().expect("spawn_food placed a food");
+            preview.push(p);
+        }
+        preview
+    }
+
+    /// Build a game from a drawn board, one text line per row:
+    /// `O` or `^v<>` head, `o` body, `*` food, `#` obstacle, `.` empty.
+    ///
+    /// The board's size overrides `cfg.width`/`cfg.height`, and its obstacles
+    /// replace `cfg.obstacles` so [`GameState::reset`] keeps them.
+    ///
+    /// Body order is traced from the head: each segment is followed by the one
+    /// `o` it touches (up, down, left or right, without wrapping) that isn't
+    /// already part of the snake. So a segment may only touch its neighbours in
+    /// the chain. The heading points from the first body segment to the head;
+    /// a lone head faces its arrow, or `cfg.start_dir` for `O`. The RNG is seeded with 0.
+    pub fn from_ascii(mut cfg: GameConfig, board: &str) -> Result<Self, AsciiBoardError> {
+        let rows: Vec<&str> = board
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        let width = rows.first().ok_or(AsciiBoardError::Empty)?.chars().count() as Coord;
+
+        let mut head = None;
+        let mut facing = None;
+        let mut body = BTreeSet::new();
+        let mut food = BTreeMap::new();
+        let mut obstacles = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            let len = row.chars().count() as Coord;
+            if len != width {
+                return Err(AsciiBoardError::RaggedRow {
+                    row: y as Coord,
+                    len,
+                    expected: width,
+                });
+            }
+            for (x, ch) in row.chars().enumerate() {
+                let at = Point::new(x as Coord, y as Coord);
+                match ch {
+                    'O' | '^' | 'v' | '<' | '>' if head.is_some() => {
+                        return Err(AsciiBoardError::ExtraHead(at));
+                    }
+                    'O' => head = Some(at),
+                    '^' | 'v' | '<' | '>' => {
+                        head = Some(at);
+                        facing = Direction::from_head_glyph(ch);
+                    }
+                    'o' => {
+                        body.insert(at);
+                    }
+                    '*' => {
+                        food.insert(at, FoodKind::Normal);
+                    }
+                    '#' => obstacles.push(at),
+                    '.' => {}
+                    ch => return Err(AsciiBoardError::UnknownCell { at, ch }),
+                }
+            }
+        }
+        let head = head.ok_or(AsciiBoardError::MissingHead)?;
+
+        let mut snake = Vec::from([head]);
+        let mut last = head;
+        loop {
+            let mut next = DIRECTIONS
+                .into_iter()
+                .map(|d| last.neighbor(d))
+                .filter(|p| body.contains(p));
+            let Some(p) = next.next() else { break };
+            if next.next().is_some() {
+                return Err(AsciiBoardError::AmbiguousBody(last));
+            }
+            body.remove(&p);
+            snake.push(p);
+            last = p;
+        }
+        if let Some(&p) = body.first() {
+            return Err(AsciiBoardError::DetachedBody(p));
+        }
+
+        cfg.width = width;
+        cfg.height = rows.len() as Coord;
+        cfg.obstacles = obstacles;
+        let dir = match snake.get(1) {
+            Some(&neck) => DIRECTIONS
+                .into_iter()
+                .find(|&d| neck.neighbor(d) == head)
+                .expect("neck is adjacent to head"),
+            None => facing.unwrap_or(cfg.start_dir),
+        };
+        let mut game = Self::with_seed(cfg, 0);
+        game.player_mut().body = snake.into_iter().collect();
+        game.player_mut().dir = dir;
+        game.foods_spawned = food.len() as u32;
+        game.food_spawn_ticks = food.keys().map(|&p| (p, 0)).collect();
+        game.food = food;
+        Ok(game)
+    }
+
+    /// Directions queued since recording began, tagged with the number of
+    /// ticks that had elapsed when each was queued.
+    pub fn input_log(&self) -> &[(u64, Direction)] {
+        &self.input_log
+    }
+
+    /// Create a new game with non-deterministic seed
+    ///
+    /// Panics if `cfg` is invalid; see [`GameState::try_new`].
+    #[cfg(feature = "std")]
+    pub fn new(cfg: GameConfig) -> Self {
+        Self::try_new(cfg).expect("invalid game config")
+    }
+
+    /// Create a new game with non-deterministic seed, validating `cfg` first.
+    #[cfg(feature = "std")]
+    pub fn try_new(cfg: GameConfig) -> Result<Self, ConfigError> {
+        cfg.validate()?;
+        Ok(Self::with_rng(cfg, ChaCha8Rng::from_os_rng()))
+    }
+
+    /// Serialize the full game, including the RNG stream position, to JSON.
+    ///
+    /// Fails for a game built with [`GameState::with_boxed_rng`], whose RNG
+    /// can't be saved.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Restore a game saved with [`GameState::to_json`].
+    #[cfg(feature = "serde")]
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    /// Current position in the food RNG's output stream.
+    ///
+    /// A seed (see [`GameState::with_seed`]) plus a word position is the canonical
+    /// way to reproduce a game's food sequence, e.g. for replays. Always 0 with
+    /// a custom RNG.
+    pub fn rng_word_pos(&self) -> u128 {
+        self.rng.word_pos()
+    }
+
+    /// Seek the food RNG to `pos`, as previously returned by [`GameState::rng_word_pos`].
+    /// Does nothing with a custom RNG.
+    pub fn set_rng_word_pos(&mut self, pos: u128) {
+        self.rng.set_word_pos(pos);
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.cfg
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    /// Why the player's snake died, or `None` while it's still alive.
+    pub fn death_cause(&self) -> Option<DeathCause> {
+        self.player().death_cause
+    }
+
+    /// The cell the player's head crashed into, if it died on a wall or
+    /// obstacle under [`DeathStyle::ShowCrash`]. Off the board for wall hits.
+    pub fn crash_site(&self) -> Option<Point> {
+        self.player().crash_site
+    }
+
+    /// A snake's crash site pulled onto the board, where rasterizers mark it.
+    fn drawn_crash_site(&self, snake: &Snake) -> Option<Point> {
+        snake.crash_site.map(|p| {
+            Point::new(
+                p.x.clamp(0, self.cfg.width - 1),
+                p.y.clamp(0, self.cfg.height - 1),
+            )
+        })
+    }
+
+    /// Every snake's on-board crash site.
+    fn drawn_crash_sites(&self) -> impl Iterator<Item = Point> + '_ {
+        self.snakes.iter().filter_map(|s| self.drawn_crash_site(s))
+    }
+
+    /// Every snake, the player first.
+    pub fn snakes(&self) -> &[Snake] {
+        &self.snakes
+    }
+
+    fn player(&self) -> &Snake {
+        &self.snakes[0]
+    }
+
+    fn player_mut(&mut self) -> &mut Snake {
+        &mut self.snakes[0]
+    }
+
+    /// Segments of every snake, living or dead.
+    fn all_segments(&self) -> impl Iterator<Item = &Point> {
+        self.snakes.iter().flat_map(|s| s.body.iter())
+    }
+
+    /// Put another snake on the board, e.g. for a second local player, and
+    /// return its index. It respawns at the same place on [`GameState::reset`].
+    ///
+    /// The body trails `len` segments behind `head`, opposite `dir`. If it
+    /// runs into the edge of the board it folds back along the neighbouring
+    /// row or column, and whatever still doesn't fit is added as pending
+    /// growth instead.
+    pub fn add_snake(&mut self, head: Point, dir: Direction, len: usize) -> usize {
+        self.snakes.push(Snake::new(head, dir, len, &self.cfg));
+        self.snakes.len() - 1
+    }
+
+    /// The player's score; see [`GameState::score_of`] for other snakes.
+    pub fn score(&self) -> u32 {
+        self.player().score
+    }
+
+    /// Score of the snake at `snake_idx`, or `None` if there's no such snake.
+    pub fn score_of(&self, snake_idx: usize) -> Option<u32> {
+        self.snakes.get(snake_idx).map(|s| s.score)
+    }
+
+    /// Highest score reached across all resets of this game.
+    pub fn high_score(&self) -> u32 {
+        self.high_score
+    }
+
+    /// Fraction of the board covered by living snakes, obstacles and food, in
+    /// `0.0..=1.0`. Handy for scaling speed or food count as the board fills up.
+    pub fn occupancy(&self) -> f32 {
+        let filled = self
+            .snakes
+            .iter()
+            .filter(|s| s.is_alive())
+            .flat_map(|s| s.body.iter())
+            .chain(self.obstacles.iter())
+            .chain(self.food.keys())
+            .collect::<BTreeSet<_>>()
+            .len();
+        let area = (self.cfg.width.max(0) * self.cfg.height.max(0)) as usize;
+        if area == 0 {
+            return 0.0;
+        }
+        filled as f32 / area as f32
+    }
+
+    /// Number of ticks that advanced the game since the last reset.
+    ///
+    /// Paused, dead and won ticks don't count; the tick that kills the snake does.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    #[deprecated(note = "renamed to `ticks`")]
+    pub fn tick_count(&self) -> u64 {
+        self.ticks()
+    }
+
+    /// The player's segments from head to tail; see [`GameState::snakes`] for the rest.
+    pub fn snake_segments(&self) -> impl Iterator<Item = &Point> {
+        self.player().segments()
+    }
+
+    pub fn food_positions(&self) -> impl Iterator<Item = &Point> {
+        self.food.keys()
+    }
+
+    /// The kind of food at `p`, if any.
+    pub fn food_kind_at(&self, p: Point) -> Option<FoodKind> {
+        self.food.get(&p).copied()
+    }
+
+    pub fn obstacle_positions(&self) -> impl Iterator<Item = &Point> {
+        self.obstacles.iter()
+    }
+
+    /// Remove all food from the board. New food only appears once some is eaten.
+    pub fn clear_food(&mut self) {
+        self.food.clear();
+        self.food_spawn_ticks.clear();
+    }
+
+    /// Put a normal food at `p`, e.g. to set up a scenario. Returns false,
+    /// placing nothing, if `p` is off the board or playable mask, or already
+    /// holds a snake, food or obstacle.
+    pub fn place_food(&mut self, p: Point) -> bool {
+        let taken = self.all_segments().any(|&s| s == p)
+            || self.food.contains_key(&p)
+            || self.obstacles.contains(&p);
+        if taken || !self.is_playable(p) {
+            return false;
+        }
+        self.food.insert(p, FoodKind::Normal);
+        self.food_spawn_ticks.insert(p, self.ticks);
+        true
+    }
+
+    /// Place an immovable wall. Cleared on [`GameState::reset`], which restores
+    /// only the obstacles listed in the config.
+    pub fn add_obstacle(&mut self, p: Point) {
+        self.obstacles.insert(p);
+    }
+
+    /// Link two cells so that entering either moves the head to the other,
+    /// keeping its direction. Portals survive [`GameState::reset`].
+    pub fn add_portal(&mut self, a: Point, b: Point) {
+        self.portals.insert(a, b);
+        self.portals.insert(b, a);
+    }
+
+    /// Carve the board into any shape: only cells in `playable` can hold
+    /// the snake or food, and the rest act like walls under
+    /// [`GameConfig::walls`]. With [`WallBehavior::Wrap`] the head skips
+    /// ahead to the next playable cell in its direction. `None` makes the
+    /// whole board playable again. The mask survives [`GameState::reset`].
+    ///
+    /// Fails, leaving the old mask in place, if a snake's starting layout
+    /// wouldn't fit inside the new one.
+    pub fn set_playable(&mut self, playable: Option<BTreeSet<Point>>) -> Result<(), ConfigError> {
+        if let Some(mask) = &playable {
+            for snake in &self.snakes {
+                let (head, dir, len) = snake.spawn;
+                let start = Snake::new(head, dir, len, &self.cfg);
+                if let Some(&p) = start.body.iter().find(|p| !mask.contains(p)) {
+                    return Err(ConfigError::StartOffMask(p));
+                }
+            }
+        }
+        self.playable = playable;
+        Ok(())
+    }
+
+    /// Whether `p` is on the board and inside the playable mask, if any.
+    pub fn is_playable(&self, p: Point) -> bool {
+        !self.out_of_bounds(p) && self.playable.as_ref().is_none_or(|m| m.contains(&p))
+    }
+
+    pub fn head(&self) -> Point {
+        self.player().head()
+    }
+
+    /// The player's current heading.
+    pub fn direction(&self) -> Direction {
+        self.player().dir
+    }
+
+    /// The queued press the next tick will take, if any. It may still be
+    /// dropped then as a 180* turn.
+    pub fn pending_direction(&self) -> Option<Direction> {
+        self.player().input_queue.front().copied()
+    }
+
+    /// Capture the current frame's state without holding a borrow on the game.
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            width: self.cfg.width,
+            height: self.cfg.height,
+            head: self.head(),
+            snake_len: self.player().body.len(),
+            score: self.score(),
+            status: self.status,
+            food: self.food.keys().copied().collect(),
+        }
+    }
+
+    /// Where the head will be after the next tick, assuming it survives.
+    ///
+    /// Applies the next queued direction (if legal), wrapping, and portals. On a
+    /// walled board a fatal move returns the off-board cell.
+    pub fn peek_next_head(&self) -> Point {
+        let player = self.player();
+        let dir = match player.input_queue.front() {
+            Some(&next) if !next.is_opposite(player.dir) => next,
+            _ => player.dir,
+        };
+        self.wrapped_target(dir)
+    }
+
+    /// The cell one step from the head in `dir`, wrapped round the edges.
+    /// Meant for [`WallBehavior::Wrap`] boards; see [`GameState::next_cell`]
+    /// for one that also says when a move leaves the board.
+    ///
+    /// Ignores the queued direction and doesn't check for collisions; a move
+    /// off a walled board or the playable mask returns the cell it would hit.
+    pub fn wrapped_target(&self, dir: Direction) -> Point {
+        let head = self.head();
+        self.step_from(head, dir).unwrap_or_else(|| {
+            let (dx, dy) = dir.dx_dy();
+            Point::new(head.x + dx, head.y + dy)
+        })
+    }
+
+    /// The cell the head lands on moving `dir`: wrapped round the edges,
+    /// past masked cells, and through any portal there.
+    ///
+    /// Ignores the queued direction and doesn't check for collisions.
+    /// `None` if the move leaves a walled board or the playable mask.
+    pub fn next_cell(&self, dir: Direction) -> Option<Point> {
+        self.step_from(self.head(), dir)
+    }
+
+    /// Whether moving `dir` next tick avoids walls (unless wrapping),
+    /// obstacles, and body segments that won't have moved out of the way.
+    pub fn is_safe(&self, dir: Direction) -> bool {
+        self.resolve_move(dir).is_ok()
+    }
+
+    /// Every direction that would kill the player next tick, in `Up, Down,
+    /// Left, Right` order. The reversal of the current heading is left out,
+    /// since it is ignored rather than fatal, as are walls under
+    /// [`WallBehavior::Stop`]. Head-on collisions with other snakes depend on
+    /// their moves and aren't considered.
+    pub fn unsafe_directions(&self) -> Vec<Direction> {
+        let heading = self.player().dir;
+        let stops = self.cfg.walls() == WallBehavior::Stop;
+        DIRECTIONS
+            .into_iter()
+            .filter(|&d| !d.is_opposite(heading))
+            .filter(|&d| match self.resolve_move(d) {
+                Ok(_) => false,
+                Err(cause) => !(stops && cause == DeathCause::Wall),
+            })
+            .collect()
+    }
+
+    /// Request a direction change. Presses are buffered (up to
+    /// [`GameConfig::input_queue_capacity`]) and applied one per tick, each
+    /// checked against the heading at that time to prevent 180° reversal.
+    ///
+    /// Repeating the last queued direction is ignored; when the buffer is
+    /// full the newest entry is replaced.
+    pub fn queue_direction(&mut self, dir: Direction) {
+        if self.recording {
+            self.input_log.push((self.ticks, dir));
+        }
+        let capacity = self.cfg.input_queue_capacity;
+        self.player_mut().queue(dir, capacity);
+    }
+
+    /// Like [`GameState::queue_direction`], for the snake at `snake_idx`.
+    /// Unknown indices are ignored. Only the player's presses are recorded.
+    pub fn queue_direction_for(&mut self, snake_idx: usize, dir: Direction) {
+        if snake_idx == 0 {
+            return self.queue_direction(dir);
+        }
+        let capacity = self.cfg.input_queue_capacity;
+        if let Some(snake) = self.snakes.get_mut(snake_idx) {
+            snake.queue(dir, capacity);
+        }
+    }
+
+    /// Queue a 90° turn relative to the current heading.
+    pub fn turn(&mut self, rot: Turn) {
+        let dir = match rot {
+            Turn::Left => self.direction().turn_left(),
+            Turn::Right => self.direction().turn_right(),
+        };
+        self.queue_direction(dir);
+    }
+
+    /// Segments the player is still owed from eating or [`GameState::grow`];
+    /// one unfurls from the tail per tick.
+    pub fn pending_growth(&self) -> usize {
+        self.player().pending_growth
+    }
+
+    /// The player's length once pending growth has unfurled: segments on the
+    /// board plus [`GameState::pending_growth`].
+    pub fn length(&self) -> usize {
+        self.player().body.len() + self.player().pending_growth
+    }
+
+    /// Lengthen the snake by `n` segments, independent of eating.
+    ///
+    /// Like real growth, the new segments unfurl from the tail one per tick
+    /// rather than appearing all at once.
+    pub fn grow(&mut self, n: usize) {
+        let player = self.player_mut();
+        player.pending_growth = player.pending_growth.saturating_add(n);
+    }
+
+    /// Replace the snake with `len` segments laid row by row from the top-left,
+    /// alternating direction each row, with the head last and heading Down.
+    ///
+    /// Meant for benchmarks that need long snakes; `len` is clamped to the board.
+    #[doc(hidden)]
+    pub fn fill_snake_for_bench(&mut self, len: usize) {
+        let w = self.cfg.width.max(1) as usize;
+        let len = len.clamp(1, w * self.cfg.height.max(1) as usize);
+        let player = self.player_mut();
+        player.body.clear();
+        for i in (0..len).rev() {
+            let (row, col) = (i / w, i % w);
+            let x = if row % 2 == 0 { col } else { w - 1 - col };
+            player.body.push_back(Point::new(x as Coord, row as Coord));
+        }
+        player.dir = Direction::Down;
+        player.input_queue.clear();
+        player.pending_growth = 0;
+        self.clear_food();
+        self.replenish_food();
+    }
+
+    /// Turn the food magnet on or off. While on, each food moves one cell
+    /// toward the head per tick, never onto the snake, obstacles, or other food.
+    pub fn set_food_drift(&mut self, enabled: bool) {
+        self.food_drift = enabled;
+    }
+
+    /// Switch edge wrapping mid-game, e.g. for a power-up. Every segment is
+    /// always on the board, so turning wrapping off just makes the walls
+    /// deadly again from the next tick on. Turning it off keeps
+    /// [`WallBehavior::Stop`] if that was set.
+    pub fn set_wrap(&mut self, wrap: bool) {
+        #[allow(deprecated)]
+        {
+            self.cfg.wrap_edges = false;
+        }
+        self.cfg.wall_behavior = match (wrap, self.cfg.wall_behavior) {
+            (true, _) => WallBehavior::Wrap,
+            (false, WallBehavior::Wrap) => WallBehavior::Die,
+            (false, walls) => walls,
+        };
+    }
+
+    /// Pause or resume the game. Has no effect once the snake is dead.
+    /// Directions queued while paused are applied on the first tick after resuming.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.status = match (self.status, paused) {
+            (GameStatus::Running, true) => GameStatus::Paused,
+            (GameStatus::Paused, false) => GameStatus::Running,
+            (status, _) => status,
+        };
+    }
+
+    /// Keep the last `capacity` pre-tick states so they can be restored with
+    /// [`GameState::rewind`]. A capacity of 0 disables history.
+    pub fn enable_history(&mut self, capacity: usize) {
+        self.history_capacity = capacity;
+        while self.history.len() > capacity {
+            self.history.pop_front();
+        }
+    }
+
+    /// Undo the most recent tick, including the RNG position and any high
+    /// score it set. A custom RNG from [`GameState::with_boxed_rng`] can't be
+    /// seeked, so it stays where it is and food may differ on the replayed tick.
+    /// Returns false if there was nothing to rewind.
+    pub fn rewind(&mut self) -> bool {
+        let Some(entry) = self.history.pop_back() else {
+            return false;
+        };
+        self.snakes = entry.snakes;
+        self.food = entry.food;
+        self.obstacles = entry.obstacles;
+        self.food_queue = entry.food_queue;
+        self.rng.set_word_pos(entry.rng_word_pos);
+        self.status = entry.status;
+        self.high_score = entry.high_score;
+        self.ticks = entry.ticks;
+        self.food_spawn_ticks = entry.food_spawn_ticks;
+        self.foods_spawned = entry.foods_spawned;
+        self.foods_eaten = entry.foods_eaten;
+        self.ticks_since_food = entry.ticks_since_food;
+        true
+    }
+
+    fn record_history(&mut self) {
+        if self.history_capacity == 0 {
+            return;
+        }
+        if self.history.len() == self.history_capacity {
+            self.history.pop_front();
+        }
+        self.history.push_back(HistoryEntry {
+            snakes: self.snakes.clone(),
+            food: self.food.clone(),
+            obstacles: self.obstacles.clone(),
+            food_queue: self.food_queue.clone(),
+            rng_word_pos: self.rng.word_pos(),
+            status: self.status,
+            high_score: self.high_score,
+            ticks: self.ticks,
+            food_spawn_ticks: self.food_spawn_ticks.clone(),
+            foods_spawned: self.foods_spawned,
+            foods_eaten: self.foods_eaten,
+            ticks_since_food: self.ticks_since_food,
+        });
+    }
+
+    /// Resets snakes, directions, food, status, and score.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.input_log.clear();
+        self.status = GameStatus::Running;
+        self.ticks = 0;
+        self.food_spawn_ticks.clear();
+        self.foods_spawned = 0;
+        self.foods_eaten = 0;
+        self.ticks_since_food = 0;
+        self.food.clear();
+        self.obstacles = self.cfg.obstacles.iter().copied().collect();
+
+        // Center the player unless told otherwise; the body trails opposite the heading.
+        let start = self
+            .cfg
+            .start_pos
+            .unwrap_or(Point::new(self.cfg.width / 2, self.cfg.height / 2));
+        let player = Snake::new(start, self.cfg.start_dir, self.cfg.initial_len, &self.cfg);
+        if self.snakes.is_empty() {
+            self.snakes.push(player);
+        } else {
+            self.snakes[0] = player;
+            for snake in &mut self.snakes[1..] {
+                snake.respawn(&self.cfg);
+            }
+        }
+
+        self.seed_maze();
+        self.replenish_food();
+    }
+
+    /// Queue `dir` (if any) and tick in one call, for lockstep or turn-based
+    /// play. Presses queued earlier still apply first, so drive the game
+    /// only through this to have each tick take exactly its own direction.
+    pub fn tick_with(&mut self, dir: Option<Direction>) -> TickResult {
+        if let Some(dir) = dir {
+            self.queue_direction(dir);
+        }
+        self.tick()
+    }
+
+    /// Advance the game by one tick and report what happened along the way.
+    ///
+    /// With several snakes, each snake's events are reported in index order.
+    pub fn tick_events(&mut self) -> Vec<GameEvent> {
+        self.tick();
+        core::mem::take(&mut self.events)
+    }
+
+    /// Advance the game by one tick.
+    ///
+    /// Every living snake moves at once. A dead snake stays on the board as an
+    /// obstacle; the game is over when no snake is left alive.
+    pub fn tick(&mut self) -> TickResult {
+        self.events.clear();
+        if self.status != GameStatus::Running {
+            return TickResult {
+                ate_food: false,
+                status: self.status,
+                score: self.score(),
+            };
+        }
+
+        self.record_history();
+        self.ticks += 1;
+
+        // Apply each living snake's oldest queued direction (if not 180*)
+        for snake in self.snakes.iter_mut().filter(|s| s.is_alive()) {
+            if let Some(next) = snake.input_queue.pop_front()
+                && !next.is_opposite(snake.dir)
+            {
+                snake.dir = next;
+            }
+        }
+
+        // Starve before moving, like any other death.
+        if self
+            .cfg
+            .max_ticks_without_food
+            .is_some_and(|limit| self.ticks_since_food >= limit)
+        {
+            for i in 0..self.snakes.len() {
+                if self.snakes[i].is_alive() {
+                    self.kill(i, DeathCause::Starvation);
+                }
+            }
+            self.status = GameStatus::Dead;
+            return TickResult {
+                ate_food: false,
+                status: self.status,
+                score: self.score(),
+            };
+        }
+
+        // Resolve every move against the pre-tick board before committing any.
+        let mut moves: Vec<Option<Result<Point, DeathCause>>> = (0..self.snakes.len())
+            .map(|i| {
+                let snake = &self.snakes[i];
+                if !snake.is_alive() {
+                    return None;
+                }
+                match self.resolve_move_for(i, snake.dir) {
+                    // Stopping walls hold the snake in place for the tick.
+                    Err(DeathCause::Wall) if self.cfg.walls() == WallBehavior::Stop => None,
+                    mv => Some(mv),
+                }
+            })
+            .collect();
+        // Heads meeting in one cell, or passing through each other, collide head-on.
+        let targets: Vec<Option<Point>> = (0..self.snakes.len())
+            .map(|i| match moves[i] {
+                Some(Ok(_) | Err(DeathCause::OtherSnake)) => {
+                    let snake = &self.snakes[i];
+                    self.step_from(snake.head(), snake.dir)
+                }
+                _ => None,
+            })
+            .collect();
+        for i in 0..targets.len() {
+            for j in i + 1..targets.len() {
+                if let (Some(a), Some(b)) = (targets[i], targets[j]) {
+                    let swapped = a == self.snakes[j].head() && b == self.snakes[i].head();
+                    if a == b || swapped {
+                        self.resolve_head_on(&mut moves, i, j, swapped);
+                    }
+                }
+            }
+        }
+
+        let scores_before: Vec<u32> = self.snakes.iter().map(|s| s.score).collect();
+        let mut ate_food = false;
+        for (i, mv) in moves.into_iter().enumerate() {
+            match mv {
+                None => {}
+                Some(Err(cause)) => self.kill(i, cause),
+                Some(Ok(next_head)) => ate_food |= self.advance(i, next_head),
+            }
+        }
+        if self.snakes.iter().all(|s| !s.is_alive()) {
+            self.status = GameStatus::Dead;
+            return TickResult {
+                ate_food,
+                status: self.status,
+                score: self.score(),
+            };
+        }
+        // One obstacle per multiple of `obstacles_per_score` crossed, placed
+        // once every snake has moved.
+        if let Some(step) = self.cfg.obstacles_per_score.filter(|&n| n > 0) {
+            let due: u32 = self
+                .snakes
+                .iter()
+                .zip(scores_before)
+                .map(|(s, before)| (s.score / step).saturating_sub(before / step))
+                .sum();
+            for _ in 0..due {
+                self.spawn_obstacle();
+            }
+        }
+        if self.board_filled() {
+            self.status = GameStatus::Won;
+        }
+
+        if ate_food {
+            self.replenish_food();
+            if self
+                .cfg
+                .total_food
+                .is_some_and(|total| self.foods_eaten >= total)
+            {
+                self.status = GameStatus::Won;
+            }
+            self.ticks_since_food = 0;
+        } else {
+            self.ticks_since_food += 1;
+        }
+        if self.food_drift {
+            self.drift_food();
+        }
+
+        TickResult {
+            ate_food,
+            status: self.status,
+            score: self.score(),
+        }
+    }
+
+    /// Drive the game for up to `ticks` ticks without a terminal.
+    ///
+    /// `policy` is consulted before each tick and may queue a direction. Stops early
+    /// once the game is no longer running, e.g. won or lost; that final tick is
+    /// included in the returned trace.
+    pub fn run_headless(
+        &mut self,
+        ticks: usize,
+        mut policy: impl FnMut(&GameState) -> Option<Direction>,
+    ) -> Vec<TickResult> {
+        let mut trace = Vec::with_capacity(ticks);
+        for _ in 0..ticks {
+            if let Some(dir) = policy(self) {
+                self.queue_direction(dir);
+            }
+            let res = self.tick();
+            trace.push(res);
+            if res.status != GameStatus::Running {
+                break;
+            }
+        }
+        trace
+    }
+
+    /// Apply [`GameConfig::head_to_head`] to a collision between snakes `i` and `j`.
+    fn resolve_head_on(
+        &self,
+        moves: &mut [Option<Result<Point, DeathCause>>],
+        i: usize,
+        j: usize,
+        swapped: bool,
+    ) {
+        let (len_i, len_j) = (self.snakes[i].body.len(), self.snakes[j].body.len());
+        let winner = match self.cfg.head_to_head {
+            HeadToHeadRule::LongerSurvives if len_i > len_j => Some(i),
+            HeadToHeadRule::LongerSurvives if len_j > len_i => Some(j),
+            _ => None,
+        };
+        for k in [i, j] {
+            moves[k] = match winner {
+                // The loser's body stays put, so a swapping winner can't move into it.
+                Some(w) if w == k && swapped => None,
+                Some(w) if w == k => moves[k],
+                _ => Some(Err(DeathCause::HeadOn)),
+            };
+        }
+    }
+
+    /// Mark snake `i` dead; its body stays on the board as an obstacle.
+    fn kill(&mut self, i: usize, cause: DeathCause) {
+        if self.cfg.death_style == DeathStyle::ShowCrash
+            && matches!(cause, DeathCause::Wall | DeathCause::Obstacle)
+        {
+            let snake = &self.snakes[i];
+            let head = snake.head();
+            let site = self
+                .step_from(head, snake.dir)
+                .unwrap_or_else(|| head.neighbor(snake.dir));
+            self.snakes[i].crash_site = Some(site);
+        }
+        self.snakes[i].death_cause = Some(cause);
+        self.emit(GameEvent::Died { cause });
+    }
+
+    /// Whether the player has grown past [`GameConfig::fill_to_win`].
+    fn board_filled(&self) -> bool {
+        let area = self.cfg.width.max(0) as usize * self.cfg.height.max(0) as usize;
+        self.cfg
+            .fill_to_win
+            .is_some_and(|fraction| area > 0 && self.length() as f32 / area as f32 >= fraction)
+    }
+
+    /// Move snake `i` onto `next_head`, eating whatever food is there.
+    /// Returns whether it ate.
+    fn advance(&mut self, i: usize, next_head: Point) -> bool {
+        let eaten = self.food.remove(&next_head);
+        let spawned = self.food_spawn_tick(&next_head);
+        self.food_spawn_ticks.remove(&next_head);
+        let from = self.snakes[i].head();
+        self.snakes[i].body.push_front(next_head);
+        self.emit(GameEvent::Moved {
+            from,
+            to: next_head,
+        });
+        if eaten.is_some() {
+            self.emit(GameEvent::Ate { at: next_head });
+        }
+
+        if eaten == Some(FoodKind::Normal) {
+            let points = self.food_points(spawned);
+            let snake = &mut self.snakes[i];
+            snake.score = snake.score.saturating_add(points);
+            snake.pending_growth = snake.pending_growth.saturating_add(self.cfg.grow_per_food);
+            let score = snake.score;
+            if i == 0 {
+                self.high_score = self.high_score.max(score);
+                if self.cfg.win_score.is_some_and(|target| score >= target) {
+                    self.status = GameStatus::Won;
+                }
+            }
+        }
+        if self.snakes[i].pending_growth > 0 {
+            self.snakes[i].pending_growth -= 1;
+            self.emit(GameEvent::Grew);
+        } else {
+            self.snakes[i].body.pop_back();
+        }
+        if eaten == Some(FoodKind::Shrink) {
+            let body = &mut self.snakes[i].body;
+            let keep = body.len().saturating_sub(self.cfg.shrink_amount);
+            body.truncate(keep.max(1));
+            let score = &mut self.snakes[i].score;
+            *score = score.saturating_sub(self.cfg.shrink_penalty);
+        }
+        if eaten.is_some() {
+            self.foods_eaten += 1;
+        }
+        eaten.is_some()
+    }
+
+    /// The tick the food at `p` appeared on.
+    fn food_spawn_tick(&self, p: &Point) -> u64 {
+        self.food_spawn_ticks.get(p).copied().unwrap_or(0)
+    }
+
+    /// Points for eating food that appeared on tick `spawned`, including any
+    /// time bonus.
+    fn food_points(&self, spawned: u64) -> u32 {
+        let bonus = self.cfg.food_bonus.map_or(0, |b| {
+            let since_spawn = self.ticks.saturating_sub(spawned);
+            let decayed = since_spawn / b.decay_ticks.max(1);
+            b.max.saturating_sub(decayed.try_into().unwrap_or(u32::MAX))
+        });
+        self.cfg.points_per_food.saturating_add(bonus)
+    }
+
+    /// The cell reached by stepping `dir` from `from`, after wrapping and
+    /// portals, or `None` if that leaves a walled board or playable mask.
+    fn step_from(&self, from: Point, dir: Direction) -> Option<Point> {
+        let (dx, dy) = dir.dx_dy();
+        let mut next = Point::new(from.x + dx, from.y + dy);
+        if self.cfg.walls() == WallBehavior::Wrap {
+            next = self.wrap(next);
+            // Skip masked cells; a full lap means nowhere else is playable.
+            let cells = self.cfg.width.max(0) as usize * self.cfg.height.max(0) as usize;
+            for _ in 0..cells {
+                if self.is_playable(next) {
+                    break;
+                }
+                next = self.wrap(next.neighbor(dir));
+            }
+        }
+        if !self.is_playable(next) {
+            return None;
+        }
+        Some(self.portals.get(&next).copied().unwrap_or(next))
+    }
+
+    /// Where the player's head lands if it moves `dir` this tick, or what would kill it.
+    fn resolve_move(&self, dir: Direction) -> Result<Point, DeathCause> {
+        self.resolve_move_for(0, dir)
+    }
+
+    /// Like [`GameState::resolve_move`], for snake `i`. Head-on collisions
+    /// depend on the other snakes' moves and are checked in `tick`.
+    fn resolve_move_for(&self, i: usize, dir: Direction) -> Result<Point, DeathCause> {
+        let snake = &self.snakes[i];
+        let next_head = self.step_from(snake.head(), dir).ok_or(DeathCause::Wall)?;
+
+        if self.obstacles.contains(&next_head) {
+            return Err(DeathCause::Obstacle);
+        }
+
+        // Self collision: allow moving onto the tail if it will move off (unless growing)
+        let grows = (self.food.get(&next_head) == Some(&FoodKind::Normal)
+            && self.cfg.grow_per_food > 0)
+            || snake.pending_growth > 0;
+        if self.collides_with_body(snake, next_head, !grows) {
+            return Err(DeathCause::SelfCollision);
+        }
+        // Other snakes' tails might not move (they could be eating), so they always block.
+        let others = self.snakes.iter().enumerate().filter(|&(j, _)| j != i);
+        if others
+            .into_iter()
+            .any(|(_, other)| other.body.contains(&next_head))
+        {
+            return Err(DeathCause::OtherSnake);
+        }
+        Ok(next_head)
+    }
+
+    fn out_of_bounds(&self, p: Point) -> bool {
+        p.x < 0 || p.x >= self.cfg.width || p.y < 0 || p.y >= self.cfg.height
+    }
+
+    fn wrap(&self, p: Point) -> Point {
+        let mut x = p.x;
+        let mut y = p.y;
+        if x < 0 {
+            x = self.cfg.width - 1;
+        } else if x >= self.cfg.width {
+            x = 0;
+        }
+        if y < 0 {
+            y = self.cfg.height - 1;
+        } else if y >= self.cfg.height {
+            y = 0;
+        }
+        Point::new(x, y)
+    }
+
+    fn collides_with_body(&self, snake: &Snake, p: Point, tail_will_move_off: bool) -> bool {
+        // If tail will move, ignore the last segment during collision check;
+        // the grace window ignores that many more regardless.
+        let skip = self
+            .cfg
+            .self_collision_grace
+            .max(usize::from(tail_will_move_off));
+        snake.body.contains_before_last(&p, skip)
+    }
+
+    fn drift_food(&mut self) {
+        let head = self.head();
+        let foods: Vec<Point> = self.food.keys().copied().collect();
+        for from in foods {
+            let dx = self.axis_delta(from.x, head.x, self.cfg.width);
+            let dy = self.axis_delta(from.y, head.y, self.cfg.height);
+            // Close the longer gap first, falling back to the other axis if blocked.
+            let mut steps = [(dx.signum(), 0), (0, dy.signum())];
+            if dy.abs() > dx.abs() {
+                steps.swap(0, 1);
+            }
+            let free = |p: &Point| {
+                !self.snakes.iter().any(|s| s.body.contains(p))
+                    && !self.food.contains_key(p)
+                    && !self.obstacles.contains(p)
+                    && self.is_playable(*p)
+            };
+            let to = steps
+                .into_iter()
+                .filter(|&step| step != (0, 0))
+                .map(|(sx, sy)| {
+                    let p = Point::new(from.x + sx, from.y + sy);
+                    if self.cfg.walls() == WallBehavior::Wrap {
+                        self.wrap(p)
+                    } else {
+                        p
+                    }
+                })
+                .find(|p| !self.out_of_bounds(*p) && free(p));
+            if let Some(to) = to
+                && let Some(kind) = self.food.remove(&from)
+            {
+                self.food.insert(to, kind);
+                if let Some(tick) = self.food_spawn_ticks.remove(&from) {
+                    self.food_spawn_ticks.insert(to, tick);
+                }
+            }
+        }
+    }
+
+    /// Signed distance from `from` to `to` along one axis, the short way around when wrapping.
+    fn axis_delta(&self, from: Coord, to: Coord, size: Coord) -> Coord {
+        let d = to - from;
+        if self.cfg.walls() == WallBehavior::Wrap && d.abs() * 2 > size {
+            d - d.signum() * size
+        } else {
+            d
+        }
+    }
+
+    /// Spawn food until `food_count` pieces are on the board or no free cell is found.
+    fn replenish_food(&mut self) {
+        while self.food.len() < self.cfg.food_count {
+            if self
+                .cfg
+                .total_food
+                .is_some_and(|total| self.foods_spawned >= total)
+            {
+                break;
+            }
+            if !self.spawn_food() {
+                break;
+            }
+        }
+    }
+
+    /// Call `cb` with every event as `tick` emits it, e.g. to play a sound on
+    /// [`GameEvent::Ate`]. Replaces any previous callback.
+    pub fn set_on_event(&mut self, cb: Box<dyn FnMut(&GameEvent)>) {
+        self.on_event = Some(EventCallback(cb));
+    }
+
+    /// Record `event` for [`GameState::tick_events`] and pass it to the callback, if any.
+    fn emit(&mut self, event: GameEvent) {
+        #[cfg(feature = "logging")]
+        if matches!(event, GameEvent::Ate { .. } | GameEvent::Died { .. }) {
+            self.log_debug(format_args!("{event:?}"));
+        }
+        if let Some(EventCallback(cb)) = &mut self.on_event {
+            cb(&event);
+        }
+        self.events.push(event);
+    }
+
+    /// Log `what` at debug level, tagged with the tick, player head and score.
+    #[cfg(feature = "logging")]
+    fn log_debug(&self, what: fmt::Arguments<'_>) {
+        log::debug!(
+            "tick {} head {:?} score {}: {what}",
+            self.ticks,
+            self.head(),
+            self.score()
+        );
+    }
+
+    /// Script where the next foods go, e.g. for a tutorial. Each spawn takes
+    /// the next queued position instead of asking the spawner, skipping any
+    /// that are off the board or occupied by then; once the queue runs dry,
+    /// spawning falls back to the RNG. Replaces any earlier queue. Food
+    /// already on the board stays where it is.
+    pub fn set_food_queue(&mut self, positions: Vec<Point>) {
+        self.food_queue = positions.into();
+    }
+
+    /// Replace the strategy that picks where food spawns.
+    pub fn set_food_spawner(&mut self, spawner: Box<dyn FoodSpawner>) {
+        self.food_spawner = spawner;
+    }
+
+    /// Scatter obstacles at [`GameConfig::maze_density`]. Draws nothing from
+    /// the RNG when the density is zero.
+    fn seed_maze(&mut self) {
+        let density = self.cfg.maze_density;
+        if density <= 0.0 {
+            return;
+        }
+        let mut keep_clear = self.occupied_cells();
+        for snake in &self.snakes {
+            keep_clear.insert(snake.head().neighbor(snake.dir));
+        }
+        let mut placed = Vec::new();
+        for y in 0..self.cfg.height {
+            for x in 0..self.cfg.width {
+                let p = Point::new(x, y);
+                if !keep_clear.contains(&p) && self.rng.random::<f32>() < density {
+                    self.obstacles.insert(p);
+                    placed.push(p);
+                }
+            }
+        }
+
+        // Wall off pockets the player can't reach, so food never spawns in one.
+        let reachable = self.reachable_from(self.head());
+        let occupied = self.occupied_cells();
+        for y in 0..self.cfg.height {
+            for x in 0..self.cfg.width {
+                let p = Point::new(x, y);
+                if !reachable.contains(&p) && !occupied.contains(&p) && !keep_clear.contains(&p) {
+                    self.obstacles.insert(p);
+                }
+            }
+        }
+
+        // Leave food somewhere to go: open up an obstacle next to the
+        // reachable area.
+        let cells = self.cfg.width as usize * self.cfg.height as usize;
+        if self.occupied_cells().len() >= cells {
+            let borders_reachable = |p: Point| {
+                DIRECTIONS
+                    .into_iter()
+                    .filter_map(|d| self.step_from(p, d))
+                    .any(|q| reachable.contains(&q))
+            };
+            if let Some(&p) = placed.iter().find(|&&p| borders_reachable(p)) {
+                self.obstacles.remove(&p);
+            }
+        }
+    }
+
+    /// Cells reachable from `start` without crossing an obstacle or leaving
+    /// the playable area. Snake bodies don't block, since they move on.
+    fn reachable_from(&self, start: Point) -> BTreeSet<Point> {
+        let mut seen = BTreeSet::from([start]);
+        let mut frontier = VecDeque::from([start]);
+        while let Some(p) = frontier.pop_front() {
+            for d in DIRECTIONS {
+                if let Some(q) = self.step_from(p, d)
+                    && !self.obstacles.contains(&q)
+                    && seen.insert(q)
+                {
+                    frontier.push_back(q);
+                }
+            }
+        }
+        seen
+    }
+
+    /// Cells new food or obstacles can't go: snakes, food, obstacles, and
+    /// anything outside the playable mask.
+    fn occupied_cells(&self) -> BTreeSet<Point> {
+        let mut occupied: BTreeSet<Point> = self
+            .all_segments()
+            .chain(self.food.keys())
+            .chain(self.obstacles.iter())
+            .copied()
+            .collect();
+        occupied.extend(self.masked_cells());
+        occupied
+    }
+
+    /// Board cells outside the playable mask; none without a mask.
+    fn masked_cells(&self) -> impl Iterator<Item = Point> + '_ {
+        self.playable.iter().flat_map(move |mask| {
+            (0..self.cfg.height)
+                .flat_map(move |y| (0..self.cfg.width).map(move |x| Point::new(x, y)))
+                .filter(move |p| !mask.contains(p))
+        })
+    }
+
+    /// Put an obstacle on a random free cell, never right in front of a
+    /// living head. Returns false if there was none.
+    fn spawn_obstacle(&mut self) -> bool {
+        let mut occupied = self.occupied_cells();
+        for snake in self.snakes.iter().filter(|s| s.is_alive()) {
+            occupied.extend(self.step_from(snake.head(), snake.dir));
+        }
+        match UniformSpawner.choose(&occupied, &self.cfg, &mut self.rng) {
+            Some(p) => self.obstacles.insert(p),
+            None => false,
+        }
+    }
+
+    /// Returns false if no free cell was found.
+    fn spawn_food(&mut self) -> bool {
+        let occupied = self.occupied_cells();
+        let placed = match self.next_queued_food(&occupied) {
+            // Scripted food skips the RNG entirely, shrink roll included.
+            Some(p) => Some((p, FoodKind::Normal)),
+            None => self
+                .random_food_cell(&occupied)
+                .map(|p| (p, self.roll_food_kind())),
+        };
+        let Some((p, kind)) = placed else {
+            return false;
+        };
+        self.food.insert(p, kind);
+        self.food_spawn_ticks.insert(p, self.ticks);
+        self.foods_spawned += 1;
+        #[cfg(feature = "logging")]
+        self.log_debug(format_args!("spawned {kind:?} food at {p:?}"));
+        true
+    }
+
+    /// Pop the next queued food position that's still free, dropping any that aren't.
+    fn next_queued_food(&mut self, occupied: &BTreeSet<Point>) -> Option<Point> {
+        while let Some(p) = self.food_queue.pop_front() {
+            if !self.out_of_bounds(p) && !occupied.contains(&p) {
+                return Some(p);
+            }
+        }
+        None
+    }
+
+    /// Ask the spawner for a free cell, preferring one at least
+    /// `min_food_distance` from the head.
+    fn random_food_cell(&mut self, occupied: &BTreeSet<Point>) -> Option<Point> {
+        let min_distance = self.cfg.min_food_distance.max(0) as u32;
+        // Rule out the cells near the head up front, so the spawner is asked
+        // at most twice per food.
+        if min_distance > 0
+            && let Some(head) = self.snakes.first().map(Snake::head)
+        {
+            let mut far = occupied.clone();
+            for y in 0..self.cfg.height {
+                for x in 0..self.cfg.width {
+                    let p = Point::new(x, y);
+                    if self.cfg.wrapped_distance(head, p) < min_distance {
+                        far.insert(p);
+                    }
+                }
+            }
+            if let Some(p) = self.food_spawner.choose(&far, &self.cfg, &mut self.rng) {
+                return Some(p);
+            }
+        }
+        // If we fail to find a spot, do nothing (grid is effectively full).
+        self.food_spawner.choose(occupied, &self.cfg, &mut self.rng)
+    }
+
+    fn roll_food_kind(&mut self) -> FoodKind {
+        let chance = self.cfg.shrink_food_chance;
+        // Only roll when enabled so the default food sequence is unchanged.
+        if chance > 0.0 && self.rng.random_bool(chance.min(1.0)) {
+            FoodKind::Shrink
+        } else {
+            FoodKind::Normal
+        }
+    }
+}
+
+/// A score/status line followed by the board in [`render::render_ascii_kinds`] glyphs.
+impl fmt::Display for GameState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "score: {}  status: {:?}", self.score(), self.status)?;
+        f.write_str(&render::render_ascii_kinds(&rasterize_game_colored(self)))
+    }
+}
+
+/// A read-only on/off grid that renderers can draw.
+///
+/// Coordinates outside `0..width` × `0..height` read as off.
+pub trait CellGrid {
+    fn width(&self) -> Coord;
+    fn height(&self) -> Coord;
+    fn get(&self, x: Coord, y: Coord) -> bool;
+}
+
+/// A lightweight "raster" to help the renderer later.
+/// Not used by the core tick logic, but makes it trivial to convert to Braille.
+#[derive(Debug, Clone)]
+pub struct Raster2D {
+    pub width: Coord,
+    pub height: Coord,
+    pub cells: Vec<bool>,
+}
+
+impl Raster2D {
+    pub fn new(width: Coord, height: Coord) -> Self {
+        let size = (width.max(0) * height.max(0)) as usize;
+        Self {
+            width,
+            height,
+            cells: vec![false; size],
+        }
+    }
+
+    #[inline]
+    fn idx(&self, x: Coord, y: Coord) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some((y * self.width + x) as usize)
+        }
+    }
+
+    pub fn set(&mut self, x: Coord, y: Coord, on: bool) {
+        if let Some(i) = self.idx(x, y) {
+            self.cells[i] = on;
+        }
+    }
+
+    pub fn get(&self, x: Coord, y: Coord) -> bool {
+        match self.idx(x, y) {
+            Some(idx) => self.cells[idx],
+            None => false,
+        }
+    }
+
+    /// Turn every cell off, keeping the allocation.
+    pub fn clear(&mut self) {
+        self.cells.fill(false);
+    }
+
+    /// Change dimensions, keeping cells that still fit at the same coordinates.
+    /// Newly exposed cells start off.
+    pub fn resize(&mut self, width: Coord, height: Coord) {
+        let mut resized = Self::new(width, height);
+        for y in 0..self.height.min(height) {
+            for x in 0..self.width.min(width) {
+                resized.set(x, y, self.get(x, y));
+            }
+        }
+        *self = resized;
+    }
+
+    /// Every cell as `(x, y, on)`, in row-major order.
+    pub fn iter_cells(&self) -> impl Iterator<Item = (Coord, Coord, bool)> + '_ {
+        let width = self.width.max(1);
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, &on)| (i as Coord % width, i as Coord / width, on))
+    }
+
+    /// Cells whose state differs from `prev`, as `(x, y, now_on)` in row-major order.
+    pub fn diff(&self, prev: &Raster2D) -> Result<Vec<(Coord, Coord, bool)>, RasterDiffError> {
+        if (self.width, self.height) != (prev.width, prev.height) {
+            return Err(RasterDiffError {
+                current: (self.width, self.height),
+                previous: (prev.width, prev.height),
+            });
+        }
+        Ok(self
+            .iter_cells()
+            .zip(prev.cells.iter())
+            .filter(|&((_, _, on), &was)| on != was)
+            .map(|(cell, _)| cell)
+            .collect())
+    }
+}
+
+/// Rasters of different sizes cannot be diffed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RasterDiffError {
+    /// `(width, height)` of the raster being diffed.
+    pub current: (Coord, Coord),
+    /// `(width, height)` of the raster it was compared against.
+    pub previous: (Coord, Coord),
+}
+
+impl fmt::Display for RasterDiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot diff a {}x{} raster against a {}x{} one",
+            self.current.0, self.current.1, self.previous.0, self.previous.1
+        )
+    }
+}
+
+impl core::error::Error for RasterDiffError {}
+
+/// JSON maps need string keys, so point-keyed maps are stored as `[key, value]` pairs.
+#[cfg(feature = "serde")]
+mod serde_pairs {
+    use alloc::collections::BTreeMap;
+    use alloc::vec::Vec;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<K, V, S>(map: &BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map.iter())
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Ord,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+pub fn rasterize_game(state: &GameState) -> Raster2D {
+    let mut r = Raster2D::new(state.cfg.width, state.cfg.height);
+    rasterize_game_into(state, &mut r);
+    r
+}
+
+/// Like [`rasterize_game`], but draws into an existing buffer so renderers can
+/// reuse one allocation across frames. The buffer is resized to the board if needed.
+pub fn rasterize_game_into(state: &GameState, r: &mut Raster2D) {
+    if r.width != state.cfg.width || r.height != state.cfg.height {
+        *r = Raster2D::new(state.cfg.width, state.cfg.height);
+    } else {
+        r.clear();
+    }
+    // Draw snakes
+    for p in state.all_segments() {
+        r.set(p.x, p.y, true);
+    }
+    for p in state.food_positions() {
+        r.set(p.x, p.y, true);
+    }
+    for p in state.obstacle_positions() {
+        r.set(p.x, p.y, true);
+    }
+    for p in state.masked_cells() {
+        r.set(p.x, p.y, true);
+    }
+    for p in state.drawn_crash_sites() {
+        r.set(p.x, p.y, true);
+    }
+}
+
+impl CellGrid for Raster2D {
+    fn width(&self) -> Coord {
+        self.width
+    }
+
+    fn height(&self) -> Coord {
+        self.height
+    }
+
+    fn get(&self, x: Coord, y: Coord) -> bool {
+        Raster2D::get(self, x, y)
+    }
+}
+
+/// Like [`Raster2D`], but packs 64 cells per word for very large boards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitRaster {
+    pub width: Coord,
+    pub height: Coord,
+    words: Vec<u64>,
+}
+
+impl BitRaster {
+    pub fn new(width: Coord, height: Coord) -> Self {
+        let size = (width.max(0) * height.max(0)) as usize;
+        Self {
+            width,
+            height,
+            words: vec![0; size.div_ceil(64)],
+        }
+    }
+
+    #[inline]
+    fn idx(&self, x: Coord, y: Coord) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some((y * self.width + x) as usize)
+        }
+    }
+
+    pub fn set(&mut self, x: Coord, y: Coord, on: bool) {
+        if let Some(i) = self.idx(x, y) {
+            let bit = 1u64 << (i % 64);
+            if on {
+                self.words[i / 64] |= bit;
+            } else {
+                self.words[i / 64] &= !bit;
+            }
+        }
+    }
+
+    pub fn get(&self, x: Coord, y: Coord) -> bool {
+        match self.idx(x, y) {
+            Some(i) => self.words[i / 64] & (1 << (i % 64)) != 0,
+            None => false,
+        }
+    }
+
+    /// Turn every cell off, keeping the allocation.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+}
+
+impl CellGrid for BitRaster {
+    fn width(&self) -> Coord {
+        self.width
+    }
+
+    fn height(&self) -> Coord {
+        self.height
+    }
+
+    fn get(&self, x: Coord, y: Coord) -> bool {
+        BitRaster::get(self, x, y)
+    }
+}
+
+/// Like [`rasterize_game`], but into a bit-packed [`BitRaster`].
+pub fn rasterize_game_bits(state: &GameState) -> BitRaster {
+    let mut r = BitRaster::new(state.cfg.width, state.cfg.height);
+    let cells = state
+        .all_segments()
+        .chain(state.food_positions())
+        .chain(state.obstacle_positions())
+        .copied()
+        .chain(state.masked_cells())
+        .chain(state.drawn_crash_sites());
+    for p in cells {
+        r.set(p.x, p.y, true);
+    }
+    r
+}
+
+/// A window onto the board, in board coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Camera {
+    /// Board column shown in the leftmost raster column
+    pub x: Coord,
+    /// Board row shown in the top raster row
+    pub y: Coord,
+    pub width: Coord,
+    pub height: Coord,
+}
+
+/// Rasterize only what `cam` can see. Cell `(0, 0)` of the result is board cell
+/// `(cam.x, cam.y)`; anything outside the window is clipped.
+pub fn rasterize_region(state: &GameState, cam: &Camera) -> Raster2D {
+    let mut r = Raster2D::new(cam.width, cam.height);
+    let cells = state
+        .all_segments()
+        .chain(state.food_positions())
+        .chain(state.obstacle_positions())
+        .copied()
+        .chain(state.masked_cells())
+        .chain(state.drawn_crash_sites());
+    for p in cells {
+        r.set(p.x - cam.x, p.y - cam.y, true);
+    }
+    r
+}
+
+/// Like [`rasterize_game`], framed by a one-cell wall for front-ends without
+/// their own border. The result is two cells wider and taller than the board,
+/// with board cell `(0, 0)` at `(1, 1)`. The frame is left dark when edges wrap,
+/// since there is no wall to hit.
+pub fn rasterize_game_with_border(state: &GameState) -> Raster2D {
+    let (w, h) = (state.cfg.width + 2, state.cfg.height + 2);
+    let mut r = rasterize_region(
+        state,
+        &Camera {
+            x: -1,
+            y: -1,
+            width: w,
+            height: h,
+        },
+    );
+    if state.cfg.walls() != WallBehavior::Wrap {
+        for x in 0..w {
+            r.set(x, 0, true);
+            r.set(x, h - 1, true);
+        }
+        for y in 0..h {
+            r.set(0, y, true);
+            r.set(w - 1, y, true);
+        }
+    }
+    r
+}
+
+/// What occupies a cell of a [`RasterColored`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CellKind {
+    #[default]
+    Empty,
+    /// A snake's head, tagged with the way it faces
+    Head(Direction),
+    Body,
+    Food,
+    Obstacle,
+    /// Where a snake crashed under [`DeathStyle::ShowCrash`]
+    Crash,
+}
+
+/// Like [`Raster2D`], but each cell records what's drawn there so renderers can color it.
+#[derive(Debug, Clone)]
+pub struct RasterColored {
+    pub width: Coord,
+    pub height: Coord,
+    pub cells: Vec<CellKind>,
+}
+
+impl RasterColored {
+    pub fn new(width: Coord, height: Coord) -> Self {
+        let size = (width.max(0) * height.max(0)) as usize;
+        Self {
+            width,
+            height,
+            cells: vec![CellKind::Empty; size],
+        }
+    }
+
+    #[inline]
+    fn idx(&self, x: Coord, y: Coord) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some((y * self.width + x) as usize)
+        }
+    }
+
+    pub fn set(&mut self, x: Coord, y: Coord, kind: CellKind) {
+        if let Some(i) = self.idx(x, y) {
+            self.cells[i] = kind;
+        }
+    }
+
+    pub fn get(&self, x: Coord, y: Coord) -> CellKind {
+        match self.idx(x, y) {
+            Some(idx) => self.cells[idx],
+            None => CellKind::Empty,
+        }
+    }
+}
+
+/// Any non-empty cell reads as on.
+impl CellGrid for RasterColored {
+    fn width(&self) -> Coord {
+        self.width
+    }
+
+    fn height(&self) -> Coord {
+        self.height
+    }
+
+    fn get(&self, x: Coord, y: Coord) -> bool {
+        RasterColored::get(self, x, y) != CellKind::Empty
+    }
+}
+
+pub fn rasterize_game_colored(state: &GameState) -> RasterColored {
+    let mut r = RasterColored::new(state.cfg.width, state.cfg.height);
+    // Cells outside the playable mask draw like walls.
+    for p in state
+        .obstacle_positions()
+        .copied()
+        .chain(state.masked_cells())
+    {
+        r.set(p.x, p.y, CellKind::Obstacle);
+    }
+    for p in state.food_positions() {
+        r.set(p.x, p.y, CellKind::Food);
+    }
+    // Bodies first so heads always win their cells.
+    for p in state.all_segments() {
+        r.set(p.x, p.y, CellKind::Body);
+    }
+    for snake in state.snakes() {
+        let head = snake.head();
+        r.set(head.x, head.y, CellKind::Head(snake.direction()));
+    }
+    // Crash marks go last: a wall crash lands on the head's own cell.
+    for p in state.drawn_crash_sites() {
+        r.set(p.x, p.y, CellKind::Crash);
+    }
+    r
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn base_game() -> GameState {
-        let cfg = GameConfig {
-            width: 10,
-            height: 8,
-            wrap_edges: false,
-            initial_len: 3,
-            braille_friendly: true,
-        };
-        GameState::with_rng(cfg, ChaCha8Rng::seed_from_u64(42))
+    fn base_game() -> GameState {
+        let cfg = GameConfig::builder()
+            .width(10)
+            .height(8)
+            .initial_len(3)
+            .build();
+        GameState::with_rng(cfg, ChaCha8Rng::seed_from_u64(42))
+    }
+
+    /// Only uses APIs available without the `std` feature.
+    #[test]
+    fn seeded_game_runs_without_std() {
+        let mut g = GameState::with_seed(GameConfig::default(), 0);
+        let trace = g.run_headless(10, |_| None);
+        assert_eq!(trace.len(), 10);
+        assert_eq!(g.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn preview_food_sequence_matches_real_spawns() {
+        let cfg = base_game().cfg;
+        let fresh = GameState::with_seed(cfg.clone(), 42);
+        let snake: Vec<Point> = fresh.snake_segments().copied().collect();
+
+        let preview = GameState::preview_food_sequence(&cfg, 42, &snake, 5);
+        assert_eq!(preview.len(), 5);
+        assert_eq!(fresh.food_positions().collect::<Vec<_>>(), [&preview[0]]);
+        assert!(preview.iter().all(|p| !snake.contains(p)));
+        assert_eq!(
+            preview,
+            GameState::preview_food_sequence(&cfg, 42, &snake, 5)
+        );
+    }
+
+    #[test]
+    fn preview_food_sequence_matches_a_maze_game() {
+        let cfg = GameConfig::builder()
+            .width(12)
+            .height(9)
+            .maze_density(0.3)
+            .build();
+        for seed in 0..5 {
+            let mut g = GameState::with_seed(cfg.clone(), seed);
+            let snake: Vec<Point> = g.snake_segments().copied().collect();
+            let preview = GameState::preview_food_sequence(&cfg, seed, &snake, 5);
+            assert_eq!(preview.len(), 5);
+            for &p in &preview {
+                assert_eq!(g.food_positions().collect::<Vec<_>>(), [&p], "seed {seed}");
+                g.clear_food();
+                g.spawn_food();
+            }
+        }
+    }
+
+    #[test]
+    fn point_add_and_sub_handle_negatives() {
+        let a = Point::new(3, -2);
+        let b = Point::new(-5, 4);
+        assert_eq!(a + b, Point::new(-2, 2));
+        assert_eq!(a - b, Point::new(8, -6));
+        assert_eq!(b - a, Point::new(-8, 6));
+        assert_eq!(a + b - b, a);
+    }
+
+    #[test]
+    fn point_neighbor_steps_one_cell() {
+        let p = Point::new(0, 0);
+        assert_eq!(p.neighbor(Direction::Up), Point::new(0, -1));
+        assert_eq!(p.neighbor(Direction::Down), Point::new(0, 1));
+        assert_eq!(p.neighbor(Direction::Left), Point::new(-1, 0));
+        assert_eq!(p.neighbor(Direction::Right), Point::new(1, 0));
+    }
+
+    #[test]
+    fn point_manhattan_is_symmetric() {
+        let a = Point::new(-3, 2);
+        let b = Point::new(4, -1);
+        assert_eq!(a.manhattan(b), 10);
+        assert_eq!(b.manhattan(a), 10);
+        assert_eq!(a.manhattan(a), 0);
+    }
+
+    #[test]
+    fn direction_turns_cover_all_headings() {
+        use Direction::*;
+        for (d, left, right, back) in [
+            (Up, Left, Right, Down),
+            (Down, Right, Left, Up),
+            (Left, Down, Up, Right),
+            (Right, Up, Down, Left),
+        ] {
+            assert_eq!(d.turn_left(), left, "{d:?}");
+            assert_eq!(d.turn_right(), right, "{d:?}");
+            assert_eq!(d.opposite(), back, "{d:?}");
+            assert_eq!(d.turn_left().turn_right(), d);
+            assert_eq!(d.turn_right().turn_right(), d.opposite());
+        }
+    }
+
+    #[test]
+    fn vim_chars_map_to_directions() {
+        assert_eq!(Direction::from_vim_char('h'), Some(Direction::Left));
+        assert_eq!(Direction::from_vim_char('j'), Some(Direction::Down));
+        assert_eq!(Direction::from_vim_char('k'), Some(Direction::Up));
+        assert_eq!(Direction::from_vim_char('l'), Some(Direction::Right));
+        for c in ['H', 'a', 'q', ' '] {
+            assert_eq!(Direction::from_vim_char(c), None);
+        }
+    }
+
+    #[test]
+    fn opposite_agrees_with_is_opposite() {
+        use Direction::*;
+        for a in [Up, Down, Left, Right] {
+            for b in [Up, Down, Left, Right] {
+                assert_eq!(a.opposite() == b, a.is_opposite(b), "{a:?} {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn turning_is_relative_to_heading() {
+        let mut g = base_game();
+        assert_eq!(g.direction(), Direction::Right);
+        g.turn(Turn::Left);
+        assert_eq!(g.pending_direction(), Some(Direction::Up));
+        g.tick();
+        assert_eq!(g.direction(), Direction::Up);
+
+        g.turn(Turn::Left);
+        g.tick();
+        assert_eq!(g.direction(), Direction::Left);
+
+        g.turn(Turn::Right);
+        g.tick();
+        assert_eq!(g.direction(), Direction::Up);
+        assert_eq!(g.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn queued_turns_apply_one_per_tick() {
+        let mut g = base_game();
+        g.queue_direction(Direction::Down);
+        g.queue_direction(Direction::Left);
+        g.tick();
+        assert_eq!(g.direction(), Direction::Down);
+        assert_eq!(g.head(), Point::new(5, 5));
+        g.tick();
+        assert_eq!(g.direction(), Direction::Left);
+        assert_eq!(g.head(), Point::new(4, 5));
+    }
+
+    #[test]
+    fn full_input_queue_replaces_newest() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .width(10)
+                .height(8)
+                .initial_len(3)
+                .input_queue_capacity(1)
+                .build(),
+            42,
+        );
+        g.queue_direction(Direction::Down);
+        g.queue_direction(Direction::Up);
+        assert_eq!(g.player().input_queue, [Direction::Up]);
+        g.tick();
+        assert_eq!(g.direction(), Direction::Up);
+    }
+
+    #[test]
+    fn next_cell_crosses_edges() {
+        let cfg = GameConfig::builder()
+            .width(10)
+            .height(8)
+            .initial_len(1)
+            .wall_behavior(WallBehavior::Wrap)
+            .build();
+        let mut g = GameState::with_seed(cfg, 42);
+        g.player_mut().body = [Point::new(9, 0)].into();
+        assert_eq!(g.next_cell(Direction::Right), Some(Point::new(0, 0)));
+        assert_eq!(g.next_cell(Direction::Up), Some(Point::new(9, 7)));
+        assert_eq!(g.next_cell(Direction::Left), Some(Point::new(8, 0)));
+        g.add_portal(Point::new(8, 0), Point::new(2, 2));
+        assert_eq!(g.next_cell(Direction::Left), Some(Point::new(2, 2)));
+
+        let mut walled = base_game();
+        walled.player_mut().body = [Point::new(9, 0)].into();
+        assert_eq!(walled.next_cell(Direction::Right), None);
+    }
+
+    #[test]
+    fn wrapped_target_crosses_edges() {
+        let cfg = GameConfig::builder()
+            .width(10)
+            .height(8)
+            .initial_len(1)
+            .wall_behavior(WallBehavior::Wrap)
+            .build();
+        let mut g = GameState::with_seed(cfg, 42);
+        g.player_mut().body = [Point::new(9, 0)].into();
+        assert_eq!(g.wrapped_target(Direction::Right), Point::new(0, 0));
+        assert_eq!(g.wrapped_target(Direction::Up), Point::new(9, 7));
+        assert_eq!(g.wrapped_target(Direction::Left), Point::new(8, 0));
+
+        let mut walled = base_game();
+        walled.player_mut().body = [Point::new(9, 0)].into();
+        assert_eq!(walled.wrapped_target(Direction::Right), Point::new(10, 0));
+    }
+
+    #[test]
+    fn initial_state_is_running() {
+        let g = base_game();
+        assert_eq!(g.status(), GameStatus::Running);
+        assert!(g.snake_segments().count() >= 1);
+        assert!(g.food_positions().count() >= 1);
+    }
+
+    #[test]
+    fn queue_direction_blocks_180() {
+        let mut g = base_game();
+        // Start going Right
+        g.queue_direction(Direction::Left); // 180° turn; should ignore
+        let before = g.direction();
+        g.tick();
+        assert_eq!(g.status(), GameStatus::Running);
+        assert_eq!(g.direction(), before);
+    }
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn head_moves() {
+        let mut g = base_game();
+        let head_1 = g.head().clone();
+        g.tick();
+        let head_2 = g.head().clone();
+        assert_ne!(head_1, head_2);
+    }
+
+    #[test]
+    fn eating_increases_score_and_length() {
+        let mut g = base_game();
+        // Place food directly in front of the head.
+        let (dx, dy) = g.direction().dx_dy();
+        let head = g.head();
+        let food_pos = Point::new(head.x + dx, head.y + dy);
+        // Clear and insert deterministic food.
+        g.clear_food();
+        assert!(g.place_food(food_pos));
+        let len_before = g.snake_segments().count();
+        let res = g.tick();
+        assert_eq!(g.head(), food_pos, "Head advanced onto food position");
+        assert!(res.ate_food, "Ate food");
+        assert_eq!(g.score(), 1, "Score incremented");
+        assert_eq!(
+            g.snake_segments().count(),
+            len_before + 1,
+            "Snake length grew"
+        );
+    }
+
+    #[test]
+    fn wall_collision_kills() {
+        let mut g = GameState::with_rng(
+            GameConfig::builder()
+                .width(3)
+                .height(3)
+                .initial_len(1)
+                .build(),
+            ChaCha8Rng::seed_from_u64(1),
+        );
+        // Put head at right edge, moving right
+        g.player_mut().body.clear();
+        g.player_mut().body.push_front(Point::new(2, 1));
+        g.player_mut().dir = Direction::Right;
+        let res = g.tick();
+        assert_eq!(res.status, GameStatus::Dead);
+    }
+
+    #[test]
+    fn self_collision_kills() {
+        // Small arena, no wrapping; we’ll craft a U-shape.
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .width(4)
+                .height(4)
+                .initial_len(1) // we'll overwrite the snake anyway
+                .build(),
+            1,
+        );
+
+        // Clear and handcraft the snake: front == head.
+        g.player_mut().body.clear();
+        g.clear_food();
+
+        // Snake shape (head→tail), coordinates:
+        // H(2,1) → (2,2) → (1,2) → (1,1) → T(1,0)
+        // Moving LEFT from head (2,1) goes to (1,1) which is body (not tail) ⇒ collision.
+        g.player_mut().body.push_back(Point::new(2, 1)); // head
+        g.player_mut().body.push_back(Point::new(2, 2));
+        g.player_mut().body.push_back(Point::new(1, 2));
+        g.player_mut().body.push_back(Point::new(1, 1));
+        g.player_mut().body.push_back(Point::new(1, 0)); // tail
+
+        g.player_mut().dir = Direction::Left;
+
+        let res = g.tick();
+        assert_eq!(
+            res.status,
+            GameStatus::Dead,
+            "expected self-collision to kill"
+        );
+    }
+
+    #[test]
+    fn curling_into_body_kills() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .width(8)
+                .height(8)
+                .initial_len(5)
+                .build(),
+            7,
+        );
+        g.clear_food();
+
+        // Head starts at (4,4) heading right with the body trailing left.
+        // Down, Left, Up brings the head back onto (3,4), a body segment.
+        g.queue_direction(Direction::Down);
+        assert_eq!(g.tick().status, GameStatus::Running);
+        g.queue_direction(Direction::Left);
+        assert_eq!(g.tick().status, GameStatus::Running);
+        g.queue_direction(Direction::Up);
+        let res = g.tick();
+        assert_eq!(res.status, GameStatus::Dead);
+        assert_eq!(g.status(), GameStatus::Dead);
+    }
+
+    #[test]
+    fn chasing_vacating_tail_survives() {
+        let mut g = base_game();
+        g.player_mut().body.clear();
+        g.clear_food();
+
+        // Square loop: moving Down from the head lands on the tail, which moves off.
+        g.player_mut().body.push_back(Point::new(1, 1)); // head
+        g.player_mut().body.push_back(Point::new(2, 1));
+        g.player_mut().body.push_back(Point::new(2, 2));
+        g.player_mut().body.push_back(Point::new(1, 2)); // tail
+        g.player_mut().dir = Direction::Down;
+
+        let res = g.tick();
+        assert_eq!(res.status, GameStatus::Running);
+        assert_eq!(g.head(), Point::new(1, 2));
+    }
+
+    #[test]
+    fn paused_tick_does_not_move() {
+        let mut g = base_game();
+        g.set_paused(true);
+        let head = g.head();
+        let res = g.tick();
+        assert_eq!(res.status, GameStatus::Paused);
+        assert!(!res.ate_food);
+        assert_eq!(res.score, g.score());
+        assert_eq!(g.head(), head);
+    }
+
+    #[test]
+    fn unpausing_applies_queued_direction() {
+        let mut g = base_game();
+        g.set_paused(true);
+        g.queue_direction(Direction::Down);
+        g.tick();
+        assert_eq!(
+            g.direction(),
+            Direction::Right,
+            "paused tick must not consume input"
+        );
+
+        g.set_paused(false);
+        let head = g.head();
+        let res = g.tick();
+        assert_eq!(res.status, GameStatus::Running);
+        assert_eq!(g.direction(), Direction::Down);
+        assert_eq!(g.head(), Point::new(head.x, head.y + 1));
+    }
+
+    #[test]
+    fn keeps_configured_food_count() {
+        let mut g = GameState::with_seed(GameConfig::builder().food_count(3).build(), 3);
+        assert_eq!(g.food_positions().count(), 3);
+
+        // Put one of the foods directly in front of the head and eat it.
+        let head = g.head();
+        let target = Point::new(head.x + 1, head.y);
+        let other = *g.food_positions().find(|&&p| p != target).unwrap();
+        g.food.remove(&other);
+        assert!(g.place_food(target));
+        let res = g.tick();
+        assert!(res.ate_food);
+        assert_eq!(g.food_positions().count(), 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip_resumes_identically() {
+        let mut g = base_game();
+        g.queue_direction(Direction::Down);
+        g.tick();
+        g.tick();
+
+        g.add_portal(Point::new(0, 0), Point::new(9, 7));
+
+        let mut loaded = GameState::from_json(&g.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.portals, g.portals);
+        assert_eq!(loaded.player().body, g.player().body);
+        assert_eq!(loaded.score(), g.score());
+        assert_eq!(loaded.direction(), g.direction());
+        assert_eq!(loaded.food, g.food);
+
+        // Same RNG stream: eating now must spawn the same next food in both.
+        let head = g.head();
+        let (dx, dy) = g.direction().dx_dy();
+        let target = Point::new(head.x + dx, head.y + dy);
+        for game in [&mut g, &mut loaded] {
+            game.clear_food();
+            assert!(game.place_food(target));
+            assert!(game.tick().ate_food);
+        }
+        assert_eq!(loaded.food, g.food);
+        assert_eq!(loaded.player().body, g.player().body);
+    }
+
+    /// Yields the same word forever, like rand's old `StepRng` with a zero step.
+    struct ConstRng(u64);
+
+    impl RngCore for ConstRng {
+        fn next_u32(&mut self) -> u32 {
+            self.0 as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            for (i, byte) in dst.iter_mut().enumerate() {
+                *byte = self.0.to_le_bytes()[i % 8];
+            }
+        }
+    }
+
+    #[test]
+    fn injected_rng_drives_food_placement() {
+        let cfg = GameConfig::builder().width(10).height(8).build();
+        let g = GameState::with_boxed_rng(cfg.clone(), Box::new(ConstRng(0)));
+        assert_eq!(
+            g.food_positions().copied().collect::<Vec<_>>(),
+            [Point::new(0, 0)]
+        );
+        assert_eq!(g.rng_word_pos(), 0);
+
+        // Every draw near the top of the range lands on the far corner.
+        let g = GameState::with_boxed_rng(cfg, Box::new(ConstRng(u64::MAX)));
+        assert_eq!(
+            g.food_positions().copied().collect::<Vec<_>>(),
+            [Point::new(9, 7)]
+        );
+        #[cfg(feature = "serde")]
+        assert!(g.to_json().is_err());
+    }
+
+    #[test]
+    fn restoring_rng_word_pos_reproduces_food() {
+        let mut g = base_game();
+        let head = g.head();
+        let target = Point::new(head.x + 1, head.y);
+        let snake = g.player().body.clone();
+        let pos = g.rng_word_pos();
+
+        g.clear_food();
+        assert!(g.place_food(target));
+        assert!(g.tick().ate_food);
+        let first = g.food.clone();
+
+        g.player_mut().body = snake;
+        g.player_mut().dir = Direction::Right;
+        g.clear_food();
+        assert!(g.place_food(target));
+        g.set_rng_word_pos(pos);
+        assert!(g.tick().ate_food);
+        assert_eq!(g.food, first);
+    }
+
+    #[test]
+    fn builder_defaults_match_default() {
+        assert_eq!(GameConfig::builder().build(), GameConfig::default());
+    }
+
+    #[test]
+    fn builder_setters_take_effect() {
+        let cfg = GameConfig::builder()
+            .width(12)
+            .height(16)
+            .wall_behavior(WallBehavior::Wrap)
+            .initial_len(2)
+            .braille_friendly(false)
+            .food_count(5)
+            .build();
+        assert_eq!(cfg.width, 12);
+        assert_eq!(cfg.height, 16);
+        assert_eq!(cfg.wall_behavior, WallBehavior::Wrap);
+        assert_eq!(cfg.initial_len, 2);
+        assert!(!cfg.braille_friendly);
+        assert_eq!(cfg.food_count, 5);
+    }
+
+    #[test]
+    fn validate_rejects_non_positive_width() {
+        let cfg = GameConfig::builder().width(0).build();
+        assert_eq!(cfg.validate(), Err(ConfigError::NonPositiveWidth(0)));
+    }
+
+    #[test]
+    fn validate_rejects_non_positive_height() {
+        let cfg = GameConfig::builder().height(-5).build();
+        assert_eq!(cfg.validate(), Err(ConfigError::NonPositiveHeight(-5)));
+    }
+
+    #[test]
+    fn validate_rejects_oversized_snake() {
+        let cfg = GameConfig::builder()
+            .width(2)
+            .height(2)
+            .initial_len(5)
+            .build();
+        assert_eq!(
+            cfg.validate(),
+            Err(ConfigError::InitialLenTooLarge { len: 5, max: 4 })
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_new_rejects_invalid_configs() {
+        for cfg in [
+            GameConfig::builder().width(0).build(),
+            GameConfig::builder().height(-5).build(),
+            GameConfig::builder()
+                .width(2)
+                .height(2)
+                .initial_len(5)
+                .build(),
+        ] {
+            assert!(GameState::try_new(cfg).is_err());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_new_accepts_valid_config() {
+        let g = GameState::try_new(GameConfig::default()).unwrap();
+        assert_eq!(g.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn narrow_board_keeps_the_starting_snake_in_bounds() {
+        let cfg = GameConfig::builder()
+            .width(4)
+            .height(3)
+            .initial_len(6)
+            .build();
+        assert_eq!(cfg.validate(), Ok(()));
+        let g = GameState::with_seed(cfg, 1);
+        let body: Vec<Point> = g.snake_segments().copied().collect();
+        assert_eq!(body.len(), 6);
+        assert!(body.iter().all(|&p| !g.out_of_bounds(p)), "{body:?}");
+        let distinct: BTreeSet<Point> = body.iter().copied().collect();
+        assert_eq!(distinct.len(), body.len());
+        assert_eq!(g.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn validate_rejects_off_board_start() {
+        let cfg = GameConfig::builder()
+            .width(4)
+            .height(3)
+            .start_pos(Some(Point::new(4, 1)))
+            .build();
+        assert_eq!(
+            cfg.validate(),
+            Err(ConfigError::StartOutOfBounds(Point::new(4, 1)))
+        );
+    }
+
+    #[test]
+    fn validate_rejects_fill_to_win_outside_unit_range() {
+        for fraction in [0.0, -0.5, 1.5, f32::NAN] {
+            let cfg = GameConfig::builder().fill_to_win(Some(fraction)).build();
+            assert_eq!(
+                cfg.validate(),
+                Err(ConfigError::FillToWinOutOfRange),
+                "{fraction}"
+            );
+        }
+        for fraction in [None, Some(0.01), Some(1.0)] {
+            let cfg = GameConfig::builder().fill_to_win(fraction).build();
+            assert_eq!(cfg.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn validate_rejects_maze_density_outside_unit_range() {
+        for density in [-0.1, 1.5, f32::NAN] {
+            let cfg = GameConfig::builder().maze_density(density).build();
+            assert_eq!(
+                cfg.validate(),
+                Err(ConfigError::MazeDensityOutOfRange),
+                "{density}"
+            );
+        }
+        for density in [0.0, 0.3, 1.0] {
+            let cfg = GameConfig::builder().maze_density(density).build();
+            assert_eq!(cfg.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn zero_initial_len_is_rejected_but_tolerated() {
+        let cfg = GameConfig::builder().initial_len(0).build();
+        assert_eq!(cfg.validate(), Err(ConfigError::ZeroInitialLen));
+        // The unchecked constructors still start with a one-segment snake.
+        let g = GameState::with_seed(cfg, 1);
+        assert_eq!(g.snake_segments().count(), 1);
+        assert_eq!(g.length(), 1);
+    }
+
+    #[test]
+    fn oversized_initial_len_folds_off_the_left_edge() {
+        let cfg = GameConfig::builder()
+            .width(6)
+            .height(4)
+            .start_pos(Some(Point::new(2, 1)))
+            .start_dir(Direction::Right)
+            .initial_len(10)
+            .build();
+        assert_eq!(cfg.validate(), Ok(()));
+        let g = GameState::with_seed(cfg, 1);
+        let body: Vec<Point> = g.snake_segments().copied().collect();
+        let expected: Vec<Point> = [
+            (2, 1),
+            (1, 1),
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+            (3, 2),
+            (4, 2),
+            (5, 2),
+            (5, 3),
+        ]
+        .into_iter()
+        .map(|(x, y)| Point::new(x, y))
+        .collect();
+        assert_eq!(body, expected);
+        assert_eq!(g.pending_growth(), 0);
+
+        // With no row to fold into, the rest arrives as growth instead.
+        let cfg = GameConfig::builder()
+            .width(5)
+            .height(1)
+            .start_pos(Some(Point::new(2, 0)))
+            .initial_len(5)
+            .build();
+        let g = GameState::with_seed(cfg, 1);
+        let body: Vec<Point> = g.snake_segments().copied().collect();
+        assert_eq!(body, [Point::new(2, 0), Point::new(1, 0), Point::new(0, 0)]);
+        assert_eq!(g.pending_growth(), 2);
+        assert_eq!(g.length(), 5);
+    }
+
+    #[test]
+    fn eating_emits_ate_then_grew() {
+        let mut g = base_game();
+        let head = g.head();
+        let target = Point::new(head.x + 1, head.y);
+        g.clear_food();
+        assert!(g.place_food(target));
+        let events = g.tick_events();
+        assert_eq!(
+            events,
+            vec![
+                GameEvent::Moved {
+                    from: head,
+                    to: target
+                },
+                GameEvent::Ate { at: target },
+                GameEvent::Grew,
+            ]
+        );
+    }
+
+    #[cfg(feature = "logging")]
+    mod logging {
+        use super::*;
+        use std::cell::RefCell;
+        use std::string::{String, ToString};
+
+        std::thread_local! {
+            /// Records logged on this thread, so parallel tests don't see each other's.
+            static RECORDS: RefCell<Vec<(log::Level, String)>> = const { RefCell::new(Vec::new()) };
+        }
+
+        struct CaptureLogger;
+
+        impl log::Log for CaptureLogger {
+            fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record<'_>) {
+                let msg = record.args().to_string();
+                RECORDS.with(|r| r.borrow_mut().push((record.level(), msg)));
+            }
+
+            fn flush(&self) {}
+        }
+
+        fn capture() {
+            static LOGGER: CaptureLogger = CaptureLogger;
+            // Only the first test to get here installs it; that's fine.
+            let _ = log::set_logger(&LOGGER);
+            log::set_max_level(log::LevelFilter::Debug);
+            RECORDS.with(|r| r.borrow_mut().clear());
+        }
+
+        #[test]
+        fn death_logs_cause_at_debug() {
+            capture();
+            let mut g = base_game();
+            g.clear_food();
+            g.player_mut().body = [Point::new(9, 4), Point::new(8, 4), Point::new(7, 4)].into();
+            g.tick();
+            let records = RECORDS.with(|r| r.take());
+            let death = records
+                .iter()
+                .find(|(_, msg)| msg.contains("Died"))
+                .expect("death was logged");
+            assert_eq!(death.0, log::Level::Debug);
+            assert_eq!(
+                death.1,
+                "tick 1 head Point { x: 9, y: 4 } score 0: Died { cause: Wall }"
+            );
+        }
+
+        #[test]
+        fn spawns_and_meals_are_logged() {
+            let mut g = base_game();
+            g.clear_food();
+            g.place_food(Point::new(6, 4));
+            capture();
+            g.tick();
+            let records = RECORDS.with(|r| r.take());
+            assert!(records[0].1.ends_with("Ate { at: Point { x: 6, y: 4 } }"));
+            assert!(records[1].1.contains("spawned Normal food at"));
+        }
+    }
+
+    #[test]
+    fn on_event_callback_sees_each_meal() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let mut g = base_game();
+        let meals = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&meals);
+        g.set_on_event(Box::new(move |event| {
+            if matches!(event, GameEvent::Ate { .. }) {
+                counter.set(counter.get() + 1);
+            }
+        }));
+        for _ in 0..2 {
+            place_food_ahead(&mut g);
+            g.tick();
+        }
+        g.clear_food();
+        g.tick();
+        assert_eq!(meals.get(), 2);
+    }
+
+    #[test]
+    fn wall_death_emits_died_wall() {
+        let mut g = base_game();
+        g.player_mut().body.clear();
+        g.player_mut().body.push_front(Point::new(9, 4));
+        let events = g.tick_events();
+        assert_eq!(
+            events,
+            vec![GameEvent::Died {
+                cause: DeathCause::Wall
+            }]
+        );
+        assert!(g.tick_events().is_empty(), "dead ticks emit nothing");
+    }
+
+    #[test]
+    fn death_cause_reports_wall() {
+        let mut g = base_game();
+        assert_eq!(g.death_cause(), None);
+        g.player_mut().body.clear();
+        g.player_mut().body.push_front(Point::new(9, 4));
+        g.tick();
+        assert_eq!(g.death_cause(), Some(DeathCause::Wall));
+        g.reset();
+        assert_eq!(g.death_cause(), None);
+    }
+
+    #[test]
+    fn death_cause_reports_self_collision() {
+        let mut g = base_game();
+        g.player_mut().body.clear();
+        g.clear_food();
+        g.player_mut().body.push_back(Point::new(2, 1)); // head
+        g.player_mut().body.push_back(Point::new(2, 2));
+        g.player_mut().body.push_back(Point::new(1, 2));
+        g.player_mut().body.push_back(Point::new(1, 1));
+        g.player_mut().body.push_back(Point::new(1, 0)); // tail
+        g.player_mut().dir = Direction::Left;
+        g.tick();
+        assert_eq!(g.death_cause(), Some(DeathCause::SelfCollision));
+    }
+
+    #[test]
+    fn self_collision_grace_ghosts_through_tail_window() {
+        for (grace, survives) in [(2, true), (1, false)] {
+            let cfg = GameConfig::builder()
+                .width(10)
+                .height(8)
+                .self_collision_grace(grace)
+                .build();
+            let mut g = GameState::with_seed(cfg, 42);
+            g.clear_food();
+            // As in `death_cause_reports_self_collision`: moving left enters
+            // the second-to-last segment.
+            g.player_mut().body = [
+                Point::new(2, 1),
+                Point::new(2, 2),
+                Point::new(1, 2),
+                Point::new(1, 1),
+                Point::new(1, 0),
+            ]
+            .into();
+            g.player_mut().dir = Direction::Left;
+            g.tick();
+            assert_eq!(g.player().is_alive(), survives, "grace {grace}");
+            if survives {
+                assert_eq!(g.head(), Point::new(1, 1));
+            } else {
+                assert_eq!(g.death_cause(), Some(DeathCause::SelfCollision));
+            }
+        }
+    }
+
+    #[test]
+    fn self_collision_grace_only_covers_the_tail_window() {
+        let cfg = GameConfig::builder()
+            .width(10)
+            .height(8)
+            .self_collision_grace(2)
+            .build();
+        let mut g = GameState::with_seed(cfg, 42);
+        g.clear_food();
+        // Moving right enters the third segment from the tail.
+        g.player_mut().body = [
+            Point::new(2, 2),
+            Point::new(2, 3),
+            Point::new(3, 3),
+            Point::new(3, 2),
+            Point::new(3, 1),
+            Point::new(4, 1),
+        ]
+        .into();
+        g.player_mut().dir = Direction::Right;
+        g.tick();
+        assert_eq!(g.death_cause(), Some(DeathCause::SelfCollision));
+    }
+
+    #[test]
+    fn death_cause_is_none_while_running() {
+        let mut g = base_game();
+        g.tick();
+        assert_eq!(g.status(), GameStatus::Running);
+        assert_eq!(g.death_cause(), None);
+    }
+
+    #[test]
+    fn headless_straight_line_hits_wall() {
+        let mut g = base_game();
+        // Head starts at (5,4) heading right on a 10-wide board: four moves, then the wall.
+        let trace = g.run_headless(20, |_| None);
+        assert_eq!(trace.len(), 5);
+        assert_eq!(trace.last().unwrap().status, GameStatus::Dead);
+        assert!(trace[..4].iter().all(|r| r.status == GameStatus::Running));
+    }
+
+    #[test]
+    fn headless_stops_once_won() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .width(10)
+                .height(8)
+                .initial_len(3)
+                .win_score(Some(1))
+                .build(),
+            42,
+        );
+        let head = g.head();
+        g.clear_food();
+        assert!(g.place_food(Point::new(head.x + 1, head.y)));
+        let trace = g.run_headless(20, |_| None);
+        assert_eq!(trace.len(), 1);
+        assert_eq!(trace[0].status, GameStatus::Won);
+    }
+
+    #[test]
+    fn points_per_food_sets_increment() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .width(10)
+                .height(8)
+                .initial_len(3)
+                .points_per_food(5)
+                .build(),
+            42,
+        );
+        let head = g.head();
+        g.clear_food();
+        assert!(g.place_food(Point::new(head.x + 1, head.y)));
+        g.tick();
+        assert_eq!(g.score(), 5);
+    }
+
+    #[test]
+    fn eating_quickly_earns_larger_bonus() {
+        let cfg = GameConfig::builder()
+            .width(20)
+            .height(8)
+            .initial_len(3)
+            .food_bonus(Some(FoodBonus {
+                max: 10,
+                decay_ticks: 1,
+            }))
+            .build();
+        let score_after = |distance: Coord| {
+            let mut g = GameState::with_seed(cfg.clone(), 42);
+            let head = g.head();
+            g.clear_food();
+            assert!(g.place_food(Point::new(head.x + distance, head.y)));
+            for _ in 0..distance {
+                g.tick();
+            }
+            assert_eq!(g.ticks(), distance as u64);
+            g.score()
+        };
+        let quick = score_after(1);
+        let slow = score_after(5);
+        assert_eq!(quick, 1 + 9);
+        assert_eq!(slow, 1 + 5);
+    }
+
+    #[test]
+    fn bonus_decays_from_each_foods_own_spawn() {
+        let cfg = GameConfig::builder()
+            .width(20)
+            .height(8)
+            .initial_len(3)
+            .food_bonus(Some(FoodBonus {
+                max: 10,
+                decay_ticks: 1,
+            }))
+            .build();
+        let mut g = GameState::with_seed(cfg, 42);
+        let head = g.head();
+        g.clear_food();
+        assert!(g.place_food(Point::new(head.x + 5, head.y)));
+        g.tick();
+        g.tick();
+        // Placed two ticks later, so eaten one tick after it appeared.
+        assert!(g.place_food(Point::new(head.x + 3, head.y)));
+        g.tick();
+        assert_eq!(g.score(), 1 + 9);
+        g.tick();
+        g.tick();
+        assert_eq!(g.score(), 1 + 9 + 1 + 5);
+    }
+
+    #[test]
+    fn huge_food_points_saturate_the_score() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .width(10)
+                .height(8)
+                .initial_len(3)
+                .points_per_food(u32::MAX)
+                .build(),
+            42,
+        );
+        for _ in 0..2 {
+            let head = g.head();
+            g.clear_food();
+            assert!(g.place_food(Point::new(head.x + 1, head.y)));
+            g.tick();
+        }
+        assert_eq!(g.score(), u32::MAX);
+        assert_eq!(g.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn hitting_obstacle_kills() {
+        let mut g = base_game();
+        let head = g.head();
+        g.clear_food();
+        g.add_obstacle(Point::new(head.x + 1, head.y));
+        let res = g.tick();
+        assert_eq!(res.status, GameStatus::Dead);
+        assert_eq!(g.death_cause(), Some(DeathCause::Obstacle));
+    }
+
+    #[test]
+    fn food_avoids_obstacles() {
+        // Wall off everything except the top row, the right column, and the
+        // snake's starting cell (2,2) so spawns must dodge obstacles.
+        let mut obstacles = Vec::new();
+        for y in 0..4 {
+            for x in 0..4 {
+                if y != 0 && x != 3 && (x, y) != (2, 2) {
+                    obstacles.push(Point::new(x, y));
+                }
+            }
+        }
+        let cfg = GameConfig::builder()
+            .width(4)
+            .height(4)
+            .initial_len(1)
+            .obstacles(obstacles.clone())
+            .build();
+        for seed in 0..20 {
+            let g = GameState::with_seed(cfg.clone(), seed);
+            assert_eq!(g.obstacle_positions().count(), obstacles.len());
+            for p in g.food_positions() {
+                assert!(!obstacles.contains(p), "food spawned on obstacle {p:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn portal_relocates_head() {
+        let mut g = base_game();
+        g.clear_food();
+        let head = g.head();
+        g.add_portal(Point::new(head.x + 1, head.y), Point::new(2, 1));
+        let res = g.tick();
+        assert_eq!(res.status, GameStatus::Running);
+        assert_eq!(g.head(), Point::new(2, 1));
+        g.tick();
+        assert_eq!(g.head(), Point::new(3, 1), "direction is preserved");
+    }
+
+    #[test]
+    fn portal_exit_blocked_by_body_kills() {
+        let mut g = base_game();
+        g.clear_food();
+        // Snake is (5,4) (4,4) (3,4); the exit lands on the middle segment.
+        g.add_portal(Point::new(6, 4), Point::new(4, 4));
+        let res = g.tick();
+        assert_eq!(res.status, GameStatus::Dead);
+        assert_eq!(g.death_cause(), Some(DeathCause::SelfCollision));
+    }
+
+    #[test]
+    fn tick_interval_clamps_to_floor() {
+        assert_eq!(tick_interval(0, 70, 1), Duration::from_millis(70));
+        assert_eq!(tick_interval(70, 70, 1), Duration::from_millis(1));
+        assert_eq!(tick_interval(1000, 70, 1), Duration::from_millis(1));
+        assert_eq!(tick_interval(30, 70, 50), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn speed_curves_never_slow_down_and_hit_the_floor() {
+        for curve in [
+            SpeedCurve::Linear,
+            SpeedCurve::Exponential,
+            SpeedCurve::Stepped,
+        ] {
+            assert_eq!(curve.interval(0, 70, 5), Duration::from_millis(70));
+            let mut last = Duration::MAX;
+            for score in 0..500 {
+                let d = curve.interval(score, 70, 5);
+                assert!(d <= last, "{curve:?} slowed down at score {score}");
+                assert!(d >= Duration::from_millis(5));
+                last = d;
+            }
+            assert_eq!(last, Duration::from_millis(5), "{curve:?}");
+            assert_eq!(curve.interval(u32::MAX, 70, 5), Duration::from_millis(5));
+        }
+        assert_eq!(
+            SpeedCurve::Exponential.interval(1, 100, 1),
+            Duration::from_millis(95)
+        );
+        assert_eq!(
+            SpeedCurve::Stepped.interval(4, 70, 1),
+            Duration::from_millis(70)
+        );
+        assert_eq!(
+            SpeedCurve::Stepped.interval(5, 70, 1),
+            Duration::from_millis(60)
+        );
+    }
+
+    #[test]
+    fn tick_interval_does_not_underflow_past_base() {
+        // The CLI starts at 70ms; scores around 70 used to underflow the subtraction.
+        assert_eq!(tick_interval(69, 70, 1), Duration::from_millis(1));
+        assert_eq!(tick_interval(70, 70, 1), Duration::from_millis(1));
+        assert_eq!(tick_interval(71, 70, 1), Duration::from_millis(1));
+        assert_eq!(tick_interval(69, 70, 0), Duration::from_millis(1));
+        assert_eq!(tick_interval(71, 70, 0), Duration::ZERO);
+        assert_eq!(tick_interval(u32::MAX, 70, 5), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn grow_unfurls_one_segment_per_tick() {
+        let mut g = base_game();
+        g.clear_food();
+        let len = g.snake_segments().count();
+        let tail = *g.snake_segments().last().unwrap();
+        g.grow(3);
+        assert_eq!(g.snake_segments().count(), len, "growth is not instant");
+        for i in 1..=4 {
+            g.tick();
+            assert_eq!(g.snake_segments().count(), len + i.min(3));
+            // Segments stay contiguous: each is one step from the next.
+            let segs: Vec<Point> = g.snake_segments().copied().collect();
+            for w in segs.windows(2) {
+                assert_eq!((w[0].x - w[1].x).abs() + (w[0].y - w[1].y).abs(), 1);
+            }
+            if i <= 3 {
+                assert_eq!(segs.last(), Some(&tail), "tail holds while growing");
+            }
+        }
+    }
+
+    #[test]
+    fn shrink_food_removes_tail_segments() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .width(10)
+                .height(8)
+                .initial_len(6)
+                .shrink_amount(2)
+                .shrink_penalty(1)
+                .build(),
+            42,
+        );
+        g.player_mut().score = 3;
+        let head = g.head();
+        let target = Point::new(head.x + 1, head.y);
+        g.clear_food();
+        g.food.insert(target, FoodKind::Shrink);
+        assert_eq!(g.food_kind_at(target), Some(FoodKind::Shrink));
+        let res = g.tick();
+        assert!(res.ate_food);
+        assert_eq!(g.head(), target);
+        assert_eq!(g.snake_segments().count(), 4);
+        assert_eq!(g.score(), 2);
+    }
+
+    #[test]
+    fn shrink_food_never_drops_below_one() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .width(10)
+                .height(8)
+                .initial_len(3)
+                .shrink_amount(10)
+                .shrink_penalty(5)
+                .build(),
+            42,
+        );
+        let head = g.head();
+        g.clear_food();
+        g.food
+            .insert(Point::new(head.x + 1, head.y), FoodKind::Shrink);
+        g.tick();
+        assert_eq!(g.snake_segments().count(), 1);
+        assert_eq!(g.score(), 0, "penalty saturates at zero");
+    }
+
+    #[test]
+    fn shrink_chance_one_spawns_only_shrink_food() {
+        let g = GameState::with_seed(
+            GameConfig::builder()
+                .food_count(4)
+                .shrink_food_chance(1.0)
+                .build(),
+            9,
+        );
+        assert!(
+            g.food_positions()
+                .all(|&p| g.food_kind_at(p) == Some(FoodKind::Shrink))
+        );
+    }
+
+    #[test]
+    fn wrapped_distance_takes_short_way_around() {
+        let plain = GameConfig::builder().width(10).height(8).build();
+        let wrapped = GameConfig::builder()
+            .width(10)
+            .height(8)
+            .wall_behavior(WallBehavior::Wrap)
+            .build();
+        let a = Point::new(0, 0);
+        let b = Point::new(9, 7);
+        assert_eq!(plain.wrapped_distance(a, b), 16);
+        assert_eq!(wrapped.wrapped_distance(a, b), 2);
+
+        // Interior points are unaffected by wrapping.
+        let c = Point::new(3, 3);
+        let d = Point::new(5, 4);
+        assert_eq!(plain.wrapped_distance(c, d), 3);
+        assert_eq!(wrapped.wrapped_distance(c, d), 3);
+    }
+
+    #[test]
+    fn rewind_restores_previous_tick() {
+        let mut g = base_game();
+        g.enable_history(4);
+        assert!(!g.rewind(), "nothing recorded yet");
+
+        let head = g.head();
+        g.clear_food();
+        assert!(g.place_food(Point::new(head.x + 1, head.y)));
+        let food_before = g.food.clone();
+        g.tick();
+        assert_eq!(g.score(), 1);
+        let food_after = g.food.clone();
+
+        assert!(g.rewind());
+        assert_eq!(g.head(), head);
+        assert_eq!(g.score(), 0);
+        assert_eq!(g.high_score(), 0);
+        assert_eq!(g.food, food_before);
+
+        // RNG position is restored too, so replaying the tick is identical.
+        g.tick();
+        assert_eq!(g.food, food_after);
+    }
+
+    #[test]
+    fn history_is_bounded_by_capacity() {
+        let mut g = base_game();
+        g.clear_food();
+        g.enable_history(2);
+        for _ in 0..3 {
+            g.tick();
+        }
+        assert!(g.rewind());
+        assert!(g.rewind());
+        assert!(!g.rewind());
+        assert_eq!(g.head(), Point::new(6, 4));
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_game() {
+        let cfg = GameConfig::builder()
+            .width(12)
+            .height(12)
+            .food_count(4)
+            .build();
+        let mut g = GameState::with_recording(cfg.clone(), 99);
+        let moves = [
+            Direction::Down,
+            Direction::Left,
+            Direction::Left,
+            Direction::Up,
+            Direction::Up,
+            Direction::Right,
+        ];
+        let mut recorded = Vec::new();
+        for &dir in moves.iter().cycle().take(12) {
+            g.queue_direction(dir);
+            recorded.push(g.tick());
+            if g.status() == GameStatus::Dead {
+                break;
+            }
+        }
+        // Coast without input until the snake hits a wall.
+        while g.status() == GameStatus::Running {
+            recorded.push(g.tick());
+        }
+        assert!(g.input_log().len() < recorded.len());
+
+        let (again, replayed) = GameState::replay(cfg, 99, g.input_log(), g.ticks());
+        assert_eq!(replayed, recorded);
+        assert_eq!(again.status(), GameStatus::Dead);
+        assert_eq!(again.player().body, g.player().body);
+        assert_eq!(again.score(), g.score());
+    }
+
+    #[test]
+    fn start_dir_lays_body_behind_head() {
+        let cases = [
+            (Direction::Up, (0, 1)),
+            (Direction::Down, (0, -1)),
+            (Direction::Left, (1, 0)),
+            (Direction::Right, (-1, 0)),
+        ];
+        for (dir, (bx, by)) in cases {
+            let mut g = GameState::with_seed(
+                GameConfig::builder()
+                    .width(10)
+                    .height(10)
+                    .initial_len(3)
+                    .start_dir(dir)
+                    .start_pos(Some(Point::new(4, 5)))
+                    .build(),
+                1,
+            );
+            let segs: Vec<Point> = g.snake_segments().copied().collect();
+            assert_eq!(
+                segs,
+                vec![
+                    Point::new(4, 5),
+                    Point::new(4 + bx, 5 + by),
+                    Point::new(4 + 2 * bx, 5 + 2 * by),
+                ],
+                "{dir:?}"
+            );
+            g.clear_food();
+            assert_eq!(g.tick().status, GameStatus::Running, "{dir:?}");
+        }
+    }
+
+    #[test]
+    fn start_pos_defaults_to_center() {
+        let g = GameState::with_seed(GameConfig::builder().width(10).height(8).build(), 1);
+        assert_eq!(g.head(), Point::new(5, 4));
+    }
+
+    /// Put a normal food directly in front of the head.
+    fn place_food_ahead(g: &mut GameState) {
+        let (dx, dy) = g.direction().dx_dy();
+        let head = g.head();
+        g.clear_food();
+        assert!(g.place_food(Point::new(head.x + dx, head.y + dy)));
+    }
+
+    #[test]
+    fn unsafe_directions_in_a_corner() {
+        let mut g = base_game();
+        g.clear_food();
+        g.player_mut().body = [Point::new(0, 0), Point::new(1, 0), Point::new(2, 0)].into();
+        g.player_mut().dir = Direction::Left;
+        assert_eq!(g.unsafe_directions(), [Direction::Up, Direction::Left]);
+
+        g.cfg.wall_behavior = WallBehavior::Stop;
+        assert_eq!(g.unsafe_directions(), []);
+        g.add_obstacle(Point::new(0, 1));
+        assert_eq!(g.unsafe_directions(), [Direction::Down]);
+    }
+
+    #[test]
+    fn scripted_run_is_pinned() {
+        // Two laps of a Hamiltonian cycle of the 8x6 board, starting from the
+        // spawn position, so the snake eats without ever hitting itself.
+        let lap = "lllkhhhhhhkllllllkhhhhhhhjjjjjlllllllkhhhhhhklll";
+        let moves: Vec<Direction> = lap
+            .chars()
+            .cycle()
+            .take(2 * lap.len())
+            .filter_map(Direction::from_vim_char)
+            .collect();
+        assert_eq!(moves.len(), 96);
+        let cfg = GameConfig::builder().width(8).height(6).build();
+        let (g, results) = GameState::scripted(cfg.clone(), 2024, &moves);
+        assert_eq!(results.len(), 96);
+        assert_eq!(g.status(), GameStatus::Running);
+        assert_eq!(g.score(), 5);
+        assert_eq!(g.head(), Point::new(4, 3), "back at the spawn cell");
+        assert_eq!(g.length(), cfg.initial_len + 5);
+        let (again, replayed) = GameState::scripted(cfg, 2024, &moves);
+        assert_eq!(results, replayed);
+        assert_eq!(again.snapshot(), g.snapshot());
+    }
+
+    #[test]
+    fn tick_with_turns_and_advances_in_one_call() {
+        let mut g = base_game();
+        g.clear_food();
+        let res = g.tick_with(Some(Direction::Down));
+        assert_eq!(res.status, GameStatus::Running);
+        assert_eq!(g.direction(), Direction::Down);
+        assert_eq!(g.head(), Point::new(5, 5));
+        g.tick_with(None);
+        assert_eq!(g.head(), Point::new(5, 6));
+    }
+
+    #[test]
+    fn direction_follows_applied_press() {
+        let mut g = base_game();
+        assert_eq!(g.direction(), Direction::Right);
+        assert_eq!(g.pending_direction(), None);
+        g.queue_direction(Direction::Up);
+        assert_eq!(g.pending_direction(), Some(Direction::Up));
+        assert_eq!(g.direction(), Direction::Right);
+        g.tick();
+        assert_eq!(g.direction(), Direction::Up);
+        assert_eq!(g.pending_direction(), None);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn wrap_edges_alias_matches_wall_behavior_wrap() {
+        let old = GameConfig::builder()
+            .width(6)
+            .height(5)
+            .wrap_edges(true)
+            .build();
+        let new = GameConfig {
+            wall_behavior: WallBehavior::Wrap,
+            ..GameConfig::builder().width(6).height(5).build()
+        };
+        assert_eq!(old.walls(), WallBehavior::Wrap);
+        let (mut a, mut b) = (GameState::with_seed(old, 8), GameState::with_seed(new, 8));
+        let presses = [
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+            Direction::Right,
+        ];
+        for &dir in presses.iter().cycle().take(40) {
+            a.queue_direction(dir);
+            b.queue_direction(dir);
+            for _ in 0..3 {
+                assert_eq!(a.tick(), b.tick());
+                assert_eq!(a.snapshot(), b.snapshot());
+            }
+        }
+    }
+
+    #[test]
+    fn food_only_spawns_inside_the_mask() {
+        let mut g = base_game();
+        // Rows 3..=5 only.
+        let mask = (0..10).flat_map(|x| (3..=5).map(move |y| Point::new(x, y)));
+        g.set_playable(Some(mask.collect())).unwrap();
+        for _ in 0..50 {
+            g.clear_food();
+            assert!(g.spawn_food());
+            let p = *g.food.keys().next().unwrap();
+            assert!(g.is_playable(p), "food at {p:?} is off the mask");
+        }
+    }
+
+    #[test]
+    fn moving_off_the_mask_dies() {
+        let mut g = base_game();
+        g.clear_food();
+        let mask = (0..10).flat_map(|x| (3..=5).map(move |y| Point::new(x, y)));
+        g.set_playable(Some(mask.collect())).unwrap();
+        g.queue_direction(Direction::Up);
+        g.tick();
+        assert_eq!(g.head(), Point::new(5, 3));
+        g.tick();
+        assert_eq!(g.death_cause(), Some(DeathCause::Wall));
+    }
+
+    #[test]
+    fn masked_cells_draw_as_walls() {
+        let mut g = base_game();
+        let mask = (0..10).flat_map(|x| (3..=5).map(move |y| Point::new(x, y)));
+        g.set_playable(Some(mask.collect())).unwrap();
+        assert!(rasterize_game(&g).get(0, 0));
+        assert!(!rasterize_game(&g).get(0, 3));
+        assert!(rasterize_game_bits(&g).get(9, 7));
+        assert_eq!(rasterize_game_colored(&g).get(0, 0), CellKind::Obstacle);
+        assert_eq!(rasterize_game_colored(&g).get(0, 3), CellKind::Empty);
+    }
+
+    #[test]
+    fn set_playable_rejects_a_mask_without_the_start() {
+        let mut g = base_game();
+        // The snake starts on row 4, from (3, 4) to (5, 4).
+        let mask: BTreeSet<Point> = (4..10).map(|x| Point::new(x, 4)).collect();
+        assert_eq!(
+            g.set_playable(Some(mask)),
+            Err(ConfigError::StartOffMask(Point::new(3, 4)))
+        );
+        assert!(g.is_playable(Point::new(0, 0)), "old mask kept");
+        g.reset();
+        assert_eq!(g.head(), Point::new(5, 4));
+    }
+
+    #[test]
+    fn wrapping_skips_to_the_next_playable_cell() {
+        let cfg = GameConfig::builder()
+            .wall_behavior(WallBehavior::Wrap)
+            .initial_len(2)
+            .build();
+        let mut g = GameState::from_ascii(cfg, "...oo>").unwrap();
+        let mask = (2..6).map(|x| Point::new(x, 0)).collect();
+        g.set_playable(Some(mask)).unwrap();
+        g.tick();
+        assert_eq!(g.status(), GameStatus::Running);
+        assert_eq!(g.head(), Point::new(2, 0));
+    }
+
+    #[test]
+    fn die_walls_kill_at_the_right_edge() {
+        let cfg = GameConfig::builder()
+            .wall_behavior(WallBehavior::Die)
+            .build();
+        let mut g = GameState::from_ascii(cfg, ".oo>").unwrap();
+        g.tick();
+        assert_eq!(g.death_cause(), Some(DeathCause::Wall));
+    }
+
+    #[test]
+    fn stop_walls_hold_the_snake_in_place() {
+        let cfg = GameConfig::builder()
+            .wall_behavior(WallBehavior::Stop)
+            .build();
+        let mut g = GameState::from_ascii(cfg, ".oo>\n....").unwrap();
+        g.tick();
+        assert_eq!(g.status(), GameStatus::Running);
+        assert_eq!(
+            g.snake_segments().copied().collect::<Vec<_>>(),
+            [Point::new(3, 0), Point::new(2, 0), Point::new(1, 0)]
+        );
+        g.queue_direction(Direction::Down);
+        g.tick();
+        assert_eq!(g.head(), Point::new(3, 1));
+    }
+
+    #[test]
+    fn wrap_walls_cross_the_right_edge() {
+        let cfg = GameConfig::builder()
+            .wall_behavior(WallBehavior::Wrap)
+            .build();
+        let mut g = GameState::from_ascii(cfg, ".oo>").unwrap();
+        g.tick();
+        assert_eq!(g.status(), GameStatus::Running);
+        assert_eq!(g.head(), Point::new(0, 0));
+    }
+
+    #[test]
+    fn set_wrap_toggles_edge_crossing() {
+        let mut g = base_game();
+        g.clear_food();
+        g.player_mut().body = [Point::new(9, 4), Point::new(8, 4), Point::new(7, 4)].into();
+        g.set_wrap(true);
+        g.tick();
+        assert_eq!(g.status(), GameStatus::Running);
+        assert_eq!(g.head(), Point::new(0, 4));
+
+        // Switching back off while the tail straddles the seam stays valid.
+        g.set_wrap(false);
+        g.tick();
+        assert_eq!(g.head(), Point::new(1, 4));
+        g.player_mut().body = [Point::new(9, 4), Point::new(8, 4), Point::new(7, 4)].into();
+        g.tick();
+        assert_eq!(g.death_cause(), Some(DeathCause::Wall));
+    }
+
+    #[test]
+    fn pending_growth_counts_down_as_segments_unfurl() {
+        let mut g = base_game();
+        g.clear_food();
+        g.grow(3);
+        assert_eq!(g.pending_growth(), 3);
+        assert_eq!(g.length(), 6);
+        for left in [2, 1, 0, 0] {
+            g.tick();
+            assert_eq!(g.pending_growth(), left);
+            assert_eq!(g.length(), 6);
+        }
+        assert_eq!(g.snake_segments().count(), 6);
+    }
+
+    #[test]
+    fn grow_per_food_unfurls_over_several_ticks() {
+        let mut g = base_game();
+        g.cfg.grow_per_food = 3;
+        place_food_ahead(&mut g);
+        g.tick();
+        g.clear_food();
+        assert_eq!(g.player().body.len(), 4);
+        g.tick();
+        g.tick();
+        assert_eq!(g.player().body.len(), 6);
+        g.tick();
+        assert_eq!(g.player().body.len(), 6, "growth stops after 3");
+    }
+
+    #[test]
+    fn obstacles_spawn_every_n_points_on_free_cells() {
+        let mut g = base_game();
+        g.cfg.obstacles_per_score = Some(2);
+        place_food_ahead(&mut g);
+        g.tick();
+        assert_eq!(g.obstacle_positions().count(), 0, "below the threshold");
+        place_food_ahead(&mut g);
+        g.tick();
+        assert_eq!(g.score(), 2);
+        assert_eq!(g.obstacle_positions().count(), 1);
+        for p in g.obstacle_positions() {
+            assert!(!g.snake_segments().any(|s| s == p));
+            assert!(!g.food_positions().any(|f| f == p));
+        }
+
+        // On a board with a single free cell, that's where it must go.
+        let mut g = GameState::from_ascii(GameConfig::default(), "oo>*\n####\n#.##").unwrap();
+        g.cfg.obstacles_per_score = Some(1);
+        g.tick();
+        assert!(g.obstacle_positions().any(|&p| p == Point::new(1, 2)));
+        assert_eq!(g.obstacle_positions().count(), 8);
+    }
+
+    #[test]
+    fn shrink_food_below_a_threshold_spawns_nothing() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .obstacles_per_score(Some(2))
+                .shrink_penalty(1)
+                .build(),
+            42,
+        );
+        g.player_mut().score = 4;
+        let ahead = g.head().neighbor(g.direction());
+        g.clear_food();
+        g.food.insert(ahead, FoodKind::Shrink);
+        g.tick();
+        assert_eq!(g.score(), 3);
+        assert_eq!(g.obstacle_positions().count(), 0);
+    }
+
+    #[test]
+    fn obstacles_never_spawn_in_front_of_a_head() {
+        for pos in 0..16 {
+            let mut g =
+                GameState::from_ascii(GameConfig::default(), "oo>*.\n#####\n##.##").unwrap();
+            g.cfg.obstacles_per_score = Some(1);
+            g.rng.set_word_pos(pos);
+            g.tick();
+            assert!(g.obstacle_positions().any(|&p| p == Point::new(2, 2)));
+            assert!(!g.obstacle_positions().any(|&p| p == Point::new(4, 0)));
+        }
+    }
+
+    #[test]
+    fn maze_density_scatters_obstacles_around_the_start() {
+        let cfg = GameConfig::builder().width(12).height(9).build();
+        for seed in 0..5 {
+            let g = GameState::with_seed(cfg.clone(), seed);
+            assert_eq!(g.obstacle_positions().count(), 0, "seed {seed}");
+        }
+
+        let cfg = GameConfig::builder()
+            .width(12)
+            .height(9)
+            .maze_density(0.3)
+            .build();
+        for seed in 0..5 {
+            let mut g = GameState::with_seed(cfg.clone(), seed);
+            for _ in 0..2 {
+                let obstacles: BTreeSet<Point> = g.obstacle_positions().copied().collect();
+                assert!(obstacles.len() > 10, "seed {seed}: {}", obstacles.len());
+                assert!(g.snake_segments().all(|p| !obstacles.contains(p)));
+                assert!(!obstacles.contains(&g.head().neighbor(g.direction())));
+                assert_eq!(g.food_positions().count(), 1);
+                // Every free cell, food included, can be reached.
+                let reachable = g.reachable_from(g.head());
+                let occupied = g.occupied_cells();
+                for p in g.food_positions().chain(g.all_segments()) {
+                    assert!(reachable.contains(p), "seed {seed}: {p:?}");
+                }
+                let cells = (0..12).flat_map(|x| (0..9).map(move |y| Point::new(x, y)));
+                for p in cells.filter(|p| !occupied.contains(p)) {
+                    assert!(reachable.contains(&p), "seed {seed}: {p:?}");
+                }
+                // A reset lays out a fresh maze.
+                g.reset();
+            }
+        }
+
+        // Even a solid maze leaves one cell for food.
+        let cfg = GameConfig::builder()
+            .width(5)
+            .height(4)
+            .initial_len(3)
+            .maze_density(1.0)
+            .build();
+        let g = GameState::with_seed(cfg, 1);
+        assert_eq!(g.obstacle_positions().count(), 20 - 3 - 1);
+        assert_eq!(g.food_positions().count(), 1);
+        assert_eq!(g.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn filling_the_board_to_the_threshold_wins() {
+        // 3 of 80 cells is under 4%; one more segment passes it.
+        let mut g = base_game();
+        g.cfg.fill_to_win = Some(0.04);
+        g.clear_food();
+        g.tick();
+        assert_eq!(g.status(), GameStatus::Running);
+        place_food_ahead(&mut g);
+        g.tick();
+        assert_eq!(g.length(), 4);
+        assert_eq!(g.status(), GameStatus::Won);
+    }
+
+    #[test]
+    fn queued_food_spawns_in_order_then_falls_back_to_rng() {
+        let mut g = base_game();
+        let ahead = [Point::new(6, 4), Point::new(7, 4)];
+        // Off the board, then on the snake: both skipped.
+        g.set_food_queue(vec![
+            Point::new(20, 0),
+            ahead[0],
+            Point::new(4, 4),
+            ahead[1],
+        ]);
+        g.clear_food();
+        g.replenish_food();
+        assert_eq!(g.food_positions().copied().collect::<Vec<_>>(), [ahead[0]]);
+        assert!(g.tick().ate_food);
+        assert_eq!(g.food_positions().copied().collect::<Vec<_>>(), [ahead[1]]);
+        assert!(g.tick().ate_food);
+        assert_eq!(g.food_positions().count(), 1, "RNG takes over");
+    }
+
+    #[test]
+    fn placed_food_is_eaten_using_only_the_public_api() {
+        let mut g = base_game();
+        g.clear_food();
+        assert_eq!(g.food_positions().count(), 0);
+        let ahead = g.next_cell(g.direction()).unwrap();
+        assert!(g.place_food(ahead));
+        assert!(!g.place_food(ahead), "already has food");
+        assert!(!g.place_food(g.head()), "snake is there");
+        assert!(!g.place_food(Point::new(-1, 0)), "off the board");
+        let res = g.tick();
+        assert!(res.ate_food);
+        assert_eq!(res.score, 1);
+        assert_eq!(g.head(), ahead);
+    }
+
+    #[test]
+    fn occupancy_counts_snake_obstacles_and_food() {
+        let mut g = base_game();
+        g.clear_food();
+        assert_eq!(g.occupancy(), 3.0 / 80.0);
+        g.obstacles.insert(Point::new(0, 0));
+        assert!(g.place_food(Point::new(9, 7)));
+        assert!(g.place_food(Point::new(0, 7)));
+        assert_eq!(g.occupancy(), 6.0 / 80.0);
+    }
+
+    #[test]
+    fn occupancy_counts_each_cell_once() {
+        let mut g = base_game();
+        g.clear_food();
+        // A coiled-up snake covers its cells only once.
+        g.player_mut().body.push_back(Point::new(3, 4));
+        // place_food won't stack food on an obstacle, so force it.
+        g.obstacles.insert(Point::new(0, 0));
+        g.food.insert(Point::new(0, 0), FoodKind::Normal);
+        assert_eq!(g.occupancy(), 4.0 / 80.0);
+
+        g.add_snake(Point::new(5, 6), Direction::Right, 3);
+        assert_eq!(g.occupancy(), 7.0 / 80.0);
+        g.snakes[1].death_cause = Some(DeathCause::Wall);
+        assert_eq!(g.occupancy(), 4.0 / 80.0, "dead snakes don't count");
+    }
+
+    #[test]
+    fn high_score_survives_reset() {
+        let mut g = GameState::with_seed(GameConfig::builder().width(20).build(), 5);
+        for _ in 0..5 {
+            place_food_ahead(&mut g);
+            g.tick();
+        }
+        assert_eq!(g.score(), 5);
+        g.reset();
+        assert_eq!(g.score(), 0);
+        for _ in 0..2 {
+            place_food_ahead(&mut g);
+            g.tick();
+        }
+        assert_eq!(g.score(), 2);
+        assert_eq!(g.high_score(), 5);
+    }
+
+    #[test]
+    fn reaching_win_score_wins() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder().width(20).win_score(Some(3)).build(),
+            5,
+        );
+        for _ in 0..2 {
+            place_food_ahead(&mut g);
+            assert_eq!(g.tick().status, GameStatus::Running);
+        }
+        place_food_ahead(&mut g);
+        let res = g.tick();
+        assert_eq!(res.status, GameStatus::Won);
+        let head = g.head();
+        g.tick();
+        assert_eq!(g.head(), head, "won games stop advancing");
+    }
+
+    #[test]
+    fn no_win_score_never_wins() {
+        let mut g = GameState::with_seed(GameConfig::builder().width(20).build(), 5);
+        for _ in 0..8 {
+            place_food_ahead(&mut g);
+            assert_eq!(g.tick().status, GameStatus::Running);
+        }
+        assert_eq!(g.score(), 8);
+    }
+
+    #[test]
+    fn drifting_food_approaches_head() {
+        let mut g = GameState::with_seed(GameConfig::builder().width(20).height(10).build(), 3);
+        let food = Point::new(17, 8);
+        g.clear_food();
+        assert!(g.place_food(food));
+        g.set_food_drift(true);
+        let cfg = g.config().clone();
+        let mut dist = cfg.wrapped_distance(g.head(), food);
+        for _ in 0..4 {
+            g.tick();
+            let food = *g.food_positions().next().unwrap();
+            let now = cfg.wrapped_distance(g.head(), food);
+            assert!(now < dist, "{now} >= {dist}");
+            dist = now;
+        }
+    }
+
+    #[test]
+    fn drifting_food_wraps_short_way() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .width(20)
+                .height(10)
+                .wall_behavior(WallBehavior::Wrap)
+                .build(),
+            3,
+        );
+        g.player_mut().body.clear();
+        g.player_mut().body.push_back(Point::new(1, 5));
+        g.player_mut().dir = Direction::Down;
+        g.clear_food();
+        assert!(g.place_food(Point::new(18, 6)));
+        g.set_food_drift(true);
+        g.tick();
+        // Head is at (1,6); the food crosses the right edge rather than walking left.
+        assert_eq!(g.food_positions().next(), Some(&Point::new(19, 6)));
+        g.tick();
+        assert_eq!(g.food_positions().next(), Some(&Point::new(0, 6)));
     }
 
     #[test]
-    fn initial_state_is_running() {
-        let g = base_game();
-        assert_eq!(g.status(), GameStatus::Running);
-        assert!(g.snake_segments().count() >= 1);
-        assert!(g.food_positions().count() >= 1);
+    fn peek_matches_actual_move() {
+        let mut g = base_game();
+        g.clear_food();
+        g.queue_direction(Direction::Up);
+        let peeked = g.peek_next_head();
+        g.tick();
+        assert_eq!(peeked, g.head());
+
+        // A 180° request is ignored by both.
+        g.queue_direction(Direction::Down);
+        let peeked = g.peek_next_head();
+        g.tick();
+        assert_eq!(peeked, g.head());
     }
 
     #[test]
-    fn queue_direction_blocks_180() {
+    fn peek_wraps_on_toroidal_board() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .width(10)
+                .height(8)
+                .wall_behavior(WallBehavior::Wrap)
+                .build(),
+            1,
+        );
+        g.clear_food();
+        g.player_mut().body.clear();
+        g.player_mut().body.push_back(Point::new(9, 3));
+        let peeked = g.peek_next_head();
+        assert_eq!(peeked, Point::new(0, 3));
+        g.tick();
+        assert_eq!(peeked, g.head());
+    }
+
+    #[test]
+    fn is_safe_flags_wall_move() {
         let mut g = base_game();
-        // Start going Right
-        g.queue_direction(Direction::Left); // 180° turn; should ignore
-        let before = g.dir;
+        g.player_mut().body.clear();
+        g.player_mut().body.push_back(Point::new(0, 3));
+        g.player_mut().dir = Direction::Up;
+        assert!(g.is_safe(Direction::Up));
+        assert!(g.is_safe(Direction::Down));
+        assert!(g.is_safe(Direction::Right));
+        assert!(!g.is_safe(Direction::Left));
+    }
+
+    #[test]
+    fn is_safe_flags_body_and_obstacles() {
+        let mut g = base_game();
+        g.clear_food();
+        // Snake (5,4) (4,4) (3,4) heading right.
+        g.add_obstacle(Point::new(5, 3));
+        assert!(!g.is_safe(Direction::Up), "obstacle");
+        assert!(!g.is_safe(Direction::Left), "neck");
+        assert!(g.is_safe(Direction::Down));
+        assert!(g.is_safe(Direction::Right));
+    }
+
+    #[test]
+    fn eating_all_total_food_wins() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder().width(20).total_food(Some(2)).build(),
+            5,
+        );
+        for _ in 0..2 {
+            assert_eq!(g.food_positions().count(), 1);
+            let food = *g.food_positions().next().unwrap();
+            g.player_mut().body.clear();
+            g.player_mut()
+                .body
+                .push_back(Point::new(food.x - 1, food.y));
+            g.player_mut().dir = Direction::Right;
+            g.tick();
+        }
+        assert_eq!(g.status(), GameStatus::Won);
+        assert_eq!(g.food_positions().count(), 0, "no third food spawns");
+    }
+
+    #[test]
+    fn min_food_distance_keeps_food_away_from_head() {
+        let cfg = GameConfig::builder()
+            .width(60)
+            .height(40)
+            .min_food_distance(20)
+            .build();
+        for seed in 0..20 {
+            let mut g = GameState::with_seed(cfg.clone(), seed);
+            for _ in 0..5 {
+                let head = g.head();
+                g.clear_food();
+                g.replenish_food();
+                let food = *g.food_positions().next().unwrap();
+                assert!(
+                    cfg.wrapped_distance(head, food) >= 20,
+                    "seed {seed}: {food:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn min_food_distance_falls_back_when_board_is_too_small() {
+        let g = GameState::with_seed(
+            GameConfig::builder()
+                .width(6)
+                .height(4)
+                .min_food_distance(100)
+                .build(),
+            3,
+        );
+        assert_eq!(g.food_positions().count(), 1);
+    }
+
+    #[test]
+    fn snake_starves_after_limit() {
+        let cfg = GameConfig::builder()
+            .width(10)
+            .height(8)
+            .initial_len(3)
+            .max_ticks_without_food(Some(3))
+            .build();
+        let mut g = GameState::with_seed(cfg, 42);
+        g.clear_food();
+        for _ in 0..3 {
+            assert_eq!(g.tick().status, GameStatus::Running);
+        }
+        let head = g.head();
+        assert_eq!(g.tick().status, GameStatus::Dead);
+        assert_eq!(g.death_cause(), Some(DeathCause::Starvation));
+        assert_eq!(g.head(), head, "starving snakes don't move");
+    }
+
+    #[test]
+    fn eating_in_time_resets_starvation() {
+        let cfg = GameConfig::builder()
+            .width(10)
+            .height(8)
+            .initial_len(3)
+            .max_ticks_without_food(Some(3))
+            .build();
+        let mut g = GameState::with_seed(cfg, 42);
+        g.clear_food();
+        assert!(g.place_food(Point::new(8, 4)));
+        for _ in 0..3 {
+            g.tick();
+        }
+        assert_eq!(g.score(), 1);
+        g.clear_food();
+        g.queue_direction(Direction::Down);
+        g.queue_direction(Direction::Left);
+        for _ in 0..3 {
+            assert_eq!(g.tick().status, GameStatus::Running);
+        }
+        assert_eq!(g.tick().status, GameStatus::Dead);
+        assert_eq!(g.death_cause(), Some(DeathCause::Starvation));
+    }
+
+    #[test]
+    fn ticks_only_count_advancing_ticks() {
+        let mut g = GameState::with_seed(
+            GameConfig::builder()
+                .width(40)
+                .height(8)
+                .initial_len(3)
+                .build(),
+            1,
+        );
+        g.clear_food();
+        for _ in 0..10 {
+            g.tick();
+        }
+        assert_eq!(g.ticks(), 10);
+
+        g.set_paused(true);
         g.tick();
-        assert_eq!(g.status(), GameStatus::Running);
-        assert_eq!(g.dir, before);
+        assert_eq!(g.ticks(), 10);
+        g.set_paused(false);
+
+        g.status = GameStatus::Dead;
+        g.tick();
+        assert_eq!(g.ticks(), 10);
+
+        g.reset();
+        assert_eq!(g.ticks(), 0);
     }
 
     #[test]
-    fn head_moves() {
+    fn very_long_snake_ticks_quickly() {
+        let cfg = GameConfig::builder()
+            .width(200)
+            .height(200)
+            .initial_len(1)
+            .build();
+        let mut g = GameState::with_seed(cfg, 0);
+        // Serpentine body filling the top 50 rows, head at (0, 50) heading Down.
+        g.player_mut().body.clear();
+        g.player_mut().body.push_back(Point::new(0, 50));
+        for y in (0..50).rev() {
+            for i in 0..200 {
+                let x = if y % 2 == 0 { i } else { 199 - i };
+                g.player_mut().body.push_back(Point::new(x, y));
+            }
+        }
+        g.player_mut().dir = Direction::Down;
+        g.clear_food();
+        for _ in 0..140 {
+            assert_eq!(g.tick().status, GameStatus::Running);
+        }
+        assert_eq!(g.snake_segments().count(), 10_001);
+        assert!(g.is_safe(Direction::Right));
+        assert!(!g.is_safe(Direction::Up));
+    }
+
+    #[test]
+    fn fill_snake_for_bench_lays_a_connected_body() {
         let mut g = base_game();
-        let head_1 = g.head().clone();
+        g.fill_snake_for_bench(25);
+        let body: Vec<Point> = g.snake_segments().copied().collect();
+        assert_eq!(body.len(), 25);
+        assert_eq!(body[0], Point::new(4, 2));
+        assert_eq!(*body.last().unwrap(), Point::new(0, 0));
+        assert!(body.windows(2).all(|w| w[0].manhattan(w[1]) == 1));
+        assert!(g.food_positions().all(|p| !body.contains(p)));
+        assert_eq!(g.tick().status, GameStatus::Running);
+    }
+
+    #[test]
+    fn show_crash_marks_the_wall_crash() {
+        let cfg = GameConfig::builder()
+            .width(10)
+            .height(8)
+            .initial_len(3)
+            .death_style(DeathStyle::ShowCrash)
+            .build();
+        let mut g = GameState::with_seed(cfg, 42);
+        g.clear_food();
+        while g.tick().status == GameStatus::Running {}
+        assert_eq!(g.death_cause(), Some(DeathCause::Wall));
+        assert_eq!(g.crash_site(), Some(Point::new(10, 4)));
+        let r = rasterize_game_colored(&g);
+        assert_eq!(r.get(9, 4), CellKind::Crash);
+        assert_eq!(r.get(8, 4), CellKind::Body);
+    }
+
+    #[test]
+    fn show_crash_marks_the_obstacle_hit() {
+        let cfg = GameConfig::builder()
+            .width(10)
+            .height(8)
+            .initial_len(3)
+            .death_style(DeathStyle::ShowCrash)
+            .build();
+        let mut g = GameState::with_seed(cfg, 42);
+        g.clear_food();
+        g.add_obstacle(Point::new(6, 4));
         g.tick();
-        let head_2 = g.head().clone();
-        assert_ne!(head_1, head_2);
+        assert_eq!(g.crash_site(), Some(Point::new(6, 4)));
+        let r = rasterize_game_colored(&g);
+        assert_eq!(r.get(6, 4), CellKind::Crash);
+        assert_eq!(r.get(5, 4), CellKind::Head(Direction::Right));
+        assert!(rasterize_game(&g).get(6, 4));
+
+        g.reset();
+        assert_eq!(g.crash_site(), None);
     }
 
     #[test]
-    fn eating_increases_score_and_length() {
+    fn freeze_style_records_no_crash_site() {
         let mut g = base_game();
-        // Place food directly in front of the head.
-        let (dx, dy) = g.dir.dx_dy();
-        let head = g.head();
-        let food_pos = Point::new(head.x + dx, head.y + dy);
-        // Clear and insert deterministic food.
-        g.food.clear();
-        g.food.insert(food_pos);
-        let len_before = g.snake_segments().count();
-        let res = g.tick();
-        assert_eq!(g.head(), food_pos, "Head advanced onto food position");
-        assert!(res.ate_food, "Ate food");
-        assert_eq!(g.score(), 1, "Score incremented");
+        g.clear_food();
+        g.add_obstacle(Point::new(6, 4));
+        g.tick();
+        assert_eq!(g.status(), GameStatus::Dead);
+        assert_eq!(g.crash_site(), None);
+        assert_eq!(rasterize_game_colored(&g).get(6, 4), CellKind::Obstacle);
+    }
+
+    #[test]
+    fn display_shows_score_line_and_board() {
+        let mut g = base_game();
+        place_food_ahead(&mut g);
+        g.tick();
+        let out = alloc::format!("{g}");
+        let mut lines = out.lines();
+        assert_eq!(lines.next(), Some("score: 1  status: Running"));
+        let board: Vec<&str> = lines.collect();
+        assert_eq!(board.len(), 8);
+        assert!(board.iter().all(|row| row.chars().count() == 10));
+        assert_eq!(board[4].chars().nth(6), Some('>'));
+    }
+
+    #[test]
+    fn from_ascii_round_trips_through_display() {
+        let level = "\
+            ......\n\
+            .#..*.\n\
+            .oo>..\n\
+            .o....\n\
+            ......";
+        let g = GameState::from_ascii(GameConfig::default(), level).unwrap();
+        assert_eq!((g.config().width, g.config().height), (6, 5));
+        assert_eq!(g.direction(), Direction::Right);
         assert_eq!(
-            g.snake_segments().count(),
-            len_before + 1,
-            "Snake length grew"
+            g.snake_segments().copied().collect::<Vec<_>>(),
+            [
+                Point::new(3, 2),
+                Point::new(2, 2),
+                Point::new(1, 2),
+                Point::new(1, 3)
+            ]
         );
+        let shown = alloc::format!("{g}");
+        let board: Vec<&str> = shown.lines().skip(1).collect();
+        assert_eq!(board, level.lines().collect::<Vec<_>>());
     }
 
     #[test]
-    fn wall_collision_kills() {
-        let mut g = GameState::with_rng(
-            GameConfig {
-                width: 3,
-                height: 3,
-                wrap_edges: false,
-                initial_len: 1,
-                braille_friendly: true,
-            },
-            ChaCha8Rng::seed_from_u64(1),
+    fn right_facing_head_renders_as_arrow() {
+        let g = GameState::from_ascii(GameConfig::default(), "oO.\n...").unwrap();
+        assert_eq!(
+            render::render_ascii_kinds(&rasterize_game_colored(&g)),
+            "o>.\n..."
         );
-        // Put head at right edge, moving right
-        g.snake.clear();
-        g.snake.push_front(Point::new(2, 1));
-        g.dir = Direction::Right;
-        let res = g.tick();
-        assert_eq!(res.status, GameStatus::Dead);
     }
 
     #[test]
-    fn self_collision_kills() {
-        // Small arena, no wrapping; we’ll craft a U-shape.
-        let mut g = GameState::with_seed(
-            GameConfig {
-                width: 4,
-                height: 4,
-                wrap_edges: false,
-                initial_len: 1,       // we'll overwrite the snake anyway
-                braille_friendly: true,
-            },
-            1,
+    fn from_ascii_lone_head_glyph_sets_heading() {
+        let g = GameState::from_ascii(GameConfig::default(), "...\n.^.").unwrap();
+        assert_eq!(g.direction(), Direction::Up);
+        let err = GameState::from_ascii(GameConfig::default(), "v.O").unwrap_err();
+        assert_eq!(err, AsciiBoardError::ExtraHead(Point::new(2, 0)));
+    }
+
+    #[test]
+    fn from_ascii_level_plays_and_resets() {
+        let mut g = GameState::from_ascii(GameConfig::default(), "o..\nO*#").unwrap();
+        assert_eq!(g.direction(), Direction::Down);
+        g.queue_direction(Direction::Right);
+        assert!(g.tick().ate_food);
+        g.reset();
+        assert_eq!(
+            g.obstacle_positions().copied().collect::<Vec<_>>(),
+            [Point::new(2, 1)]
         );
+    }
 
-        // Clear and handcraft the snake: front == head.
-        g.snake.clear();
-        g.food.clear();
+    #[test]
+    fn from_ascii_rejects_bad_boards() {
+        let cfg = GameConfig::default();
+        let parse = |s| GameState::from_ascii(cfg.clone(), s).unwrap_err();
+        assert_eq!(parse(""), AsciiBoardError::Empty);
+        assert_eq!(
+            parse("..\n..."),
+            AsciiBoardError::RaggedRow {
+                row: 1,
+                len: 3,
+                expected: 2
+            }
+        );
+        assert_eq!(
+            parse(".x"),
+            AsciiBoardError::UnknownCell {
+                at: Point::new(1, 0),
+                ch: 'x'
+            }
+        );
+        assert_eq!(parse("o."), AsciiBoardError::MissingHead);
+        assert_eq!(parse("O.O"), AsciiBoardError::ExtraHead(Point::new(2, 0)));
+        assert_eq!(
+            parse("oOo"),
+            AsciiBoardError::AmbiguousBody(Point::new(1, 0))
+        );
+        assert_eq!(
+            parse("Oo.o"),
+            AsciiBoardError::DetachedBody(Point::new(3, 0))
+        );
+    }
 
-        // Snake shape (head→tail), coordinates:
-        // H(2,1) → (2,2) → (1,2) → (1,1) → T(1,0)
-        // Moving LEFT from head (2,1) goes to (1,1) which is body (not tail) ⇒ collision.
-        g.snake.push_back(Point::new(2, 1)); // head
-        g.snake.push_back(Point::new(2, 2));
-        g.snake.push_back(Point::new(1, 2));
-        g.snake.push_back(Point::new(1, 1));
-        g.snake.push_back(Point::new(1, 0)); // tail
+    #[test]
+    fn snakes_move_independently() {
+        // The player starts at (10, 5) heading Right.
+        let cfg = GameConfig::builder()
+            .width(20)
+            .height(10)
+            .initial_len(3)
+            .build();
+        let mut g = GameState::with_seed(cfg, 3);
+        g.clear_food();
+        g.add_snake(Point::new(4, 2), Direction::Down, 3);
+        g.queue_direction(Direction::Up);
+        g.queue_direction_for(1, Direction::Left);
+        g.tick();
+        assert_eq!(g.head(), Point::new(10, 4));
+        assert_eq!(g.snakes()[1].head(), Point::new(3, 2));
+        assert_eq!(g.snakes()[1].direction(), Direction::Left);
+        assert_eq!(g.snakes()[1].segments().count(), 3);
+        assert_eq!(g.status(), GameStatus::Running);
 
-        g.dir = Direction::Left;
+        let r = rasterize_game_colored(&g);
+        assert_eq!(r.get(3, 2), CellKind::Head(Direction::Left));
+        assert_eq!(r.get(4, 2), CellKind::Body);
+    }
 
-        let res = g.tick();
-        assert_eq!(res.status, GameStatus::Dead, "expected self-collision to kill");
+    #[test]
+    fn head_on_collision_kills_both_snakes() {
+        let cfg = GameConfig::builder()
+            .width(20)
+            .height(10)
+            .initial_len(3)
+            .build();
+        let mut g = GameState::with_seed(cfg, 3);
+        g.clear_food();
+        g.add_snake(Point::new(12, 5), Direction::Left, 3);
+        g.tick();
+        assert_eq!(g.death_cause(), Some(DeathCause::HeadOn));
+        assert_eq!(g.snakes()[1].death_cause(), Some(DeathCause::HeadOn));
+        assert_eq!(g.status(), GameStatus::Dead);
+    }
+
+    #[test]
+    fn swapping_heads_is_a_head_on_collision() {
+        let cfg = GameConfig::builder()
+            .width(20)
+            .height(10)
+            .initial_len(3)
+            .build();
+        let mut g = GameState::with_seed(cfg, 3);
+        g.clear_food();
+        g.add_snake(Point::new(11, 5), Direction::Left, 3);
+        g.tick();
+        assert_eq!(g.death_cause(), Some(DeathCause::HeadOn));
+        assert_eq!(g.snakes()[1].death_cause(), Some(DeathCause::HeadOn));
+    }
+
+    #[test]
+    fn running_into_another_body_kills_only_the_mover() {
+        // Snake 1 lies across the player's path at x = 11, heading Down.
+        let cfg = GameConfig::builder()
+            .width(20)
+            .height(10)
+            .initial_len(3)
+            .build();
+        let mut g = GameState::with_seed(cfg, 3);
+        g.clear_food();
+        g.add_snake(Point::new(11, 7), Direction::Down, 3);
+        g.tick();
+        assert_eq!(g.death_cause(), Some(DeathCause::OtherSnake));
+        assert!(g.snakes()[1].is_alive());
+        assert_eq!(g.status(), GameStatus::Running);
+
+        // The dead player stays put while the other snake carries on.
+        let head = g.head();
+        g.tick();
+        assert_eq!(g.head(), head);
+        assert_eq!(g.snakes()[1].head(), Point::new(11, 9));
+
+        g.reset();
+        assert!(g.snakes().iter().all(Snake::is_alive));
+        assert_eq!(g.snakes()[1].head(), Point::new(11, 7));
+    }
+
+    #[test]
+    fn only_the_eating_snake_scores() {
+        let cfg = GameConfig::builder()
+            .width(20)
+            .height(10)
+            .initial_len(3)
+            .win_score(Some(1))
+            .build();
+        let mut g = GameState::with_seed(cfg, 3);
+        g.clear_food();
+        g.add_snake(Point::new(4, 2), Direction::Down, 3);
+        assert!(g.place_food(Point::new(4, 3)));
+        g.tick();
+        assert_eq!(g.score_of(1), Some(1));
+        assert_eq!(g.score_of(0), Some(0));
+        assert_eq!(g.score_of(2), None);
+        assert_eq!(g.score(), 0);
+        assert_eq!(g.high_score(), 0, "high score follows the player");
+        assert_eq!(g.status(), GameStatus::Running, "only the player can win");
+    }
+
+    #[test]
+    fn longer_snake_survives_head_on() {
+        let cfg = GameConfig::builder()
+            .width(20)
+            .height(10)
+            .initial_len(3)
+            .head_to_head(HeadToHeadRule::LongerSurvives)
+            .build();
+        let mut g = GameState::with_seed(cfg, 3);
+        g.clear_food();
+        g.add_snake(Point::new(12, 5), Direction::Left, 3);
+        // Player (length 4) and snake 1 (length 3) both aim at (11, 5).
+        g.player_mut().body.push_back(Point::new(7, 5));
+        g.tick();
+        assert!(g.snakes()[0].is_alive());
+        assert_eq!(g.head(), Point::new(11, 5));
+        assert_eq!(g.snakes()[1].death_cause(), Some(DeathCause::HeadOn));
+        assert_eq!(g.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn equal_lengths_both_die_head_on() {
+        let cfg = GameConfig::builder()
+            .width(20)
+            .height(10)
+            .initial_len(3)
+            .head_to_head(HeadToHeadRule::LongerSurvives)
+            .build();
+        let mut g = GameState::with_seed(cfg, 3);
+        g.clear_food();
+        g.add_snake(Point::new(12, 5), Direction::Left, 3);
+        g.tick();
+        assert_eq!(g.death_cause(), Some(DeathCause::HeadOn));
+        assert_eq!(g.snakes()[1].death_cause(), Some(DeathCause::HeadOn));
+    }
+
+    #[test]
+    fn longer_snake_swapping_heads_stays_put() {
+        let cfg = GameConfig::builder()
+            .width(20)
+            .height(10)
+            .initial_len(3)
+            .head_to_head(HeadToHeadRule::LongerSurvives)
+            .build();
+        let mut g = GameState::with_seed(cfg, 3);
+        g.clear_food();
+        g.add_snake(Point::new(11, 5), Direction::Left, 3);
+        g.player_mut().body.push_back(Point::new(7, 5));
+        g.tick();
+        assert!(g.snakes()[0].is_alive());
+        assert_eq!(g.head(), Point::new(10, 5));
+        assert_eq!(g.snakes()[1].death_cause(), Some(DeathCause::HeadOn));
+    }
+
+    #[test]
+    fn snapshot_matches_getters() {
+        let mut g = base_game();
+        g.tick();
+        let snap = g.snapshot();
+        assert_eq!(snap.width, 10);
+        assert_eq!(snap.height, 8);
+        assert_eq!(snap.head, g.head());
+        assert_eq!(snap.snake_len, g.snake_segments().count());
+        assert_eq!(snap.food.len(), g.food_positions().count());
+        assert_eq!(snap.score, g.score());
+        assert_eq!(snap.status, g.status());
+    }
+
+    #[test]
+    fn colored_raster_tags_head_body_and_food() {
+        let g = base_game();
+        let r = rasterize_game_colored(&g);
+        let mut segments = g.snake_segments();
+        let head = segments.next().unwrap();
+        assert_eq!(r.get(head.x, head.y), CellKind::Head(Direction::Right));
+        for p in segments {
+            assert_eq!(r.get(p.x, p.y), CellKind::Body);
+        }
+        for p in g.food_positions() {
+            assert_eq!(r.get(p.x, p.y), CellKind::Food);
+        }
+        let lit = r.cells.iter().filter(|&&c| c != CellKind::Empty).count();
+        assert_eq!(lit, g.snake_segments().count() + g.food_positions().count());
+    }
+
+    #[test]
+    fn colored_raster_marks_obstacles() {
+        let mut g = base_game();
+        g.add_obstacle(Point::new(0, 0));
+        let r = rasterize_game_colored(&g);
+        assert_eq!(r.get(0, 0), CellKind::Obstacle);
+        assert_eq!(render::render_ascii_kinds(&r).chars().next(), Some('#'));
+        assert!(rasterize_game(&g).get(0, 0));
+    }
+
+    #[test]
+    fn iter_cells_visits_every_cell_in_order() {
+        let mut r = Raster2D::new(5, 3);
+        r.set(0, 0, true);
+        r.set(4, 1, true);
+        r.set(2, 2, true);
+        let cells: Vec<_> = r.iter_cells().collect();
+        assert_eq!(cells.len(), 15);
+        assert_eq!(cells[0], (0, 0, true));
+        assert_eq!(cells[1], (1, 0, false));
+        assert_eq!(cells[5], (0, 1, false));
+        for (x, y, on) in cells {
+            assert_eq!(on, r.get(x, y), "({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn diff_after_one_move_lists_only_head_and_tail() {
+        let mut g = base_game();
+        let prev = rasterize_game(&g);
+        let tail = *g.player().body.back().unwrap();
+        g.tick();
+        let next = rasterize_game(&g);
+        let d = next.diff(&prev).unwrap();
+        assert_eq!(d.len(), 2);
+        assert!(d.contains(&(g.head().x, g.head().y, true)));
+        assert!(d.contains(&(tail.x, tail.y, false)));
+        assert!(next.diff(&next).unwrap().is_empty());
+    }
+
+    #[test]
+    fn diff_rejects_mismatched_dimensions() {
+        let err = Raster2D::new(4, 3).diff(&Raster2D::new(4, 2)).unwrap_err();
+        assert_eq!(err.current, (4, 3));
+        assert_eq!(err.previous, (4, 2));
+    }
+
+    #[test]
+    fn region_outside_snake_is_empty() {
+        let mut g = base_game();
+        g.clear_food();
+        let cam = Camera {
+            x: 0,
+            y: 0,
+            width: 3,
+            height: 3,
+        };
+        let r = rasterize_region(&g, &cam);
+        assert_eq!((r.width, r.height), (3, 3));
+        assert!(r.cells.iter().all(|&on| !on));
+    }
+
+    #[test]
+    fn region_clips_partially_visible_snake() {
+        let mut g = base_game();
+        g.clear_food();
+        // Snake is (5,4) (4,4) (3,4); the window covers columns 4..=6 of rows 3..=4.
+        let cam = Camera {
+            x: 4,
+            y: 3,
+            width: 3,
+            height: 2,
+        };
+        let r = rasterize_region(&g, &cam);
+        let lit: Vec<_> = r.iter_cells().filter(|&(_, _, on)| on).collect();
+        assert_eq!(lit, [(0, 1, true), (1, 1, true)]);
+    }
+
+    #[test]
+    fn border_lights_corners_only_without_wrap() {
+        let mut g = base_game();
+        g.clear_food();
+        let r = rasterize_game_with_border(&g);
+        assert_eq!((r.width, r.height), (12, 10));
+        for (x, y) in [(0, 0), (11, 0), (0, 9), (11, 9)] {
+            assert!(r.get(x, y), "corner ({x}, {y})");
+        }
+        assert!(r.get(6, 5), "head shifted inside the frame");
+        assert!(!r.get(1, 1));
+
+        g.set_wrap(true);
+        let r = rasterize_game_with_border(&g);
+        for (x, y) in [(0, 0), (11, 0), (0, 9), (11, 9)] {
+            assert!(!r.get(x, y), "corner ({x}, {y})");
+        }
+        assert!(r.get(6, 5));
+    }
+
+    #[test]
+    fn bit_raster_matches_raster2d() {
+        let mut g = base_game();
+        g.add_obstacle(Point::new(0, 0));
+        g.add_obstacle(Point::new(9, 7));
+        let bytes = rasterize_game(&g);
+        let bits = rasterize_game_bits(&g);
+        assert_eq!((bits.width, bits.height), (bytes.width, bytes.height));
+        for (x, y, on) in bytes.iter_cells() {
+            assert_eq!(bits.get(x, y), on, "({x}, {y})");
+        }
+        assert!(!bits.get(-1, 0));
+        assert!(!bits.get(10, 0));
+        assert_eq!(
+            render::render_braille(&bits),
+            render::render_braille(&bytes)
+        );
+    }
+
+    #[test]
+    fn bit_raster_set_and_clear_cross_word_boundaries() {
+        let mut r = BitRaster::new(13, 11);
+        for i in [0, 63, 64, 127, 142] {
+            r.set(i % 13, i / 13, true);
+        }
+        assert!(r.get(63 % 13, 63 / 13));
+        assert!(r.get(64 % 13, 64 / 13));
+        assert!(!r.get(65 % 13, 65 / 13));
+        r.set(64 % 13, 64 / 13, false);
+        assert!(!r.get(64 % 13, 64 / 13));
+        assert!(r.get(63 % 13, 63 / 13));
+        r.clear();
+        assert!(!r.get(0, 0));
+    }
+
+    #[test]
+    fn resize_larger_preserves_cells() {
+        let mut r = Raster2D::new(3, 2);
+        r.set(2, 1, true);
+        r.resize(6, 5);
+        assert_eq!(r.cells.len(), 30);
+        assert!(r.get(2, 1));
+        assert_eq!(r.iter_cells().filter(|&(_, _, on)| on).count(), 1);
+    }
+
+    #[test]
+    fn resize_smaller_drops_outside_cells() {
+        let mut r = Raster2D::new(4, 4);
+        r.set(1, 1, true);
+        r.set(3, 0, true);
+        r.set(0, 3, true);
+        r.resize(2, 2);
+        assert_eq!(r.cells.len(), 4);
+        assert!(r.get(1, 1));
+        assert_eq!(r.iter_cells().filter(|&(_, _, on)| on).count(), 1);
+
+        // Growing back doesn't resurrect dropped cells.
+        r.resize(4, 4);
+        assert!(!r.get(3, 0));
+        assert!(!r.get(0, 3));
+    }
+
+    #[test]
+    fn clear_turns_cells_off() {
+        let mut r = Raster2D::new(3, 3);
+        r.set(1, 1, true);
+        r.set(2, 0, true);
+        r.clear();
+        assert_eq!(r.cells.len(), 9);
+        assert!(r.cells.iter().all(|&c| !c));
+    }
+
+    #[test]
+    fn rasterize_into_reused_buffer_matches_fresh() {
+        let mut g = base_game();
+        let mut buf = Raster2D::new(1, 1);
+        rasterize_game_into(&g, &mut buf);
+        g.tick();
+        g.tick();
+        rasterize_game_into(&g, &mut buf);
+        let fresh = rasterize_game(&g);
+        assert_eq!((buf.width, buf.height), (fresh.width, fresh.height));
+        assert_eq!(buf.cells, fresh.cells);
+    }
+
+    #[test]
+    fn raster_rejects_out_of_range_edges() {
+        let mut r = Raster2D::new(4, 3);
+        r.set(4, 0, true);
+        r.set(0, 3, true);
+        assert!(r.cells.iter().all(|&c| !c), "edge writes must be no-ops");
+        assert_eq!(r.idx(4, 0), None);
+        assert_eq!(r.idx(0, 3), None);
+        assert!(!r.get(4, 0));
+        assert!(!r.get(0, 3));
+    }
+
+    #[test]
+    fn raster_last_cell_maps_to_end() {
+        let mut r = Raster2D::new(4, 3);
+        r.set(3, 2, true);
+        assert_eq!(r.idx(3, 2), Some(r.cells.len() - 1));
+        assert!(r.cells[r.cells.len() - 1]);
+        assert!(r.get(3, 2));
     }
 }
@@ -2,7 +2,10 @@ use std::io;
 use std::time::{Duration, Instant};
 
 use hjkl_snake::render::render_braille;
-use hjkl_snake::{Direction, GameConfig, GameState, rasterize_game};
+use hjkl_snake::{
+    Direction, GameConfig, GameState, GameStatus, Raster2D, SpeedCurve, Turn, WallBehavior,
+    rasterize_game_into,
+};
 
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
@@ -18,6 +21,15 @@ use ratatui::{
 };
 
 fn main() -> io::Result<()> {
+    // Read the flags first so a bad one is reported on a normal terminal.
+    let args = match Args::from_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(2);
+        }
+    };
+
     // --- Init terminal ---
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -26,7 +38,7 @@ fn main() -> io::Result<()> {
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
-    let res = run(&mut terminal);
+    let res = run(&mut terminal, &args);
 
     // --- Restore terminal even on error ---
     disable_raw_mode()?;
@@ -41,20 +53,26 @@ fn main() -> io::Result<()> {
 }
 
 const INIT_TICK_MILLIS: u64 = 70;
+const MIN_TICK_MILLIS: u64 = 1;
 
-fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
+fn run(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    args: &Args,
+) -> io::Result<()> {
+    let bindings = &args.bindings;
     // --- Game setup ---
-    let cfg = GameConfig {
-        width: 100,  // grid cells (not characters)
-        height: 40, // choose even/4-friendly for Braille density
-        wrap_edges: true,
-        initial_len: 6,
-        braille_friendly: true,
-    };
+    let cfg = GameConfig::builder()
+        .width(100) // grid cells (not characters)
+        .height(40) // choose even/4-friendly for Braille density
+        .wall_behavior(WallBehavior::Wrap)
+        .initial_len(6)
+        .speed_curve(args.curve)
+        .build();
     let mut game = GameState::new(cfg);
+    let mut raster = Raster2D::new(game.config().width, game.config().height);
 
     // Timing
-    let mut tick_rate = Duration::from_millis(INIT_TICK_MILLIS); 
+    let mut tick_rate = Duration::from_millis(INIT_TICK_MILLIS);
     let mut last_tick = Instant::now();
 
     // UI state
@@ -67,10 +85,11 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()>
             .checked_sub(now.saturating_duration_since(last_tick))
             .unwrap_or(Duration::from_millis(0));
 
+        #[allow(clippy::collapsible_if)]
         if event::poll(timeout)? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
-                    if handle_key(&mut game, key) {
+                    if handle_key(&mut game, key, bindings) {
                         running = false; // requested quit
                     }
                 }
@@ -78,12 +97,16 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()>
         }
 
         // --- Tick ---
-        if last_tick.elapsed() >= tick_rate {
+        // Paused games skip ticks but still read input above, so `p` can resume.
+        if game.status() == GameStatus::Paused {
+            last_tick = Instant::now();
+        } else if last_tick.elapsed() >= tick_rate {
             game.tick();
             last_tick = Instant::now();
         }
 
-        tick_rate = Duration::from_millis(std::cmp::max(INIT_TICK_MILLIS - game.score() as u64, 1));
+        let curve = game.config().speed_curve;
+        tick_rate = curve.interval(game.score(), INIT_TICK_MILLIS, MIN_TICK_MILLIS);
 
         // --- Render ---
         terminal.draw(|f| {
@@ -91,8 +114,10 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()>
 
             // Compose title/status
             let status = match game.status() {
-                hjkl_snake::GameStatus::Running => "󱔎  ",
-                hjkl_snake::GameStatus::Dead =>    "    (press q to quit) ",
+                hjkl_snake::GameStatus::Running => "󱔎  ".to_string(),
+                hjkl_snake::GameStatus::Paused => format!("PAUSED (press {} to resume) ", bindings.pause),
+                hjkl_snake::GameStatus::Dead =>    format!("    (press {} to quit) ", bindings.quit),
+                hjkl_snake::GameStatus::Won => format!("you win! (press {} to quit) ", bindings.quit),
             };
             let title = format!(
                 " hjkl Snake — score: {}  •  {}",
@@ -101,7 +126,8 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()>
             );
 
             // Convert to Braille string (each line is Braille cells)
-            let braille = render_braille(&rasterize_game(&game));
+            rasterize_game_into(&game, &mut raster);
+            let braille = render_braille(&raster);
 
             let block = Block::default().borders(Borders::ALL).title(title.bold());
 
@@ -134,33 +160,294 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()>
     Ok(())
 }
 
+/// What a key press asks the game to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Move(Direction),
+    Turn(Turn),
+    Quit,
+    Reset,
+    Pause,
+}
+
+/// Remappable keys, set with `--up <key>`, `--quit <key>` etc. on the command
+/// line. Arrow keys, Ctrl-C and `a`/`d` turning always work on top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KeyBindings {
+    up: KeyCode,
+    down: KeyCode,
+    left: KeyCode,
+    right: KeyCode,
+    quit: KeyCode,
+    reset: KeyCode,
+    pause: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            up: KeyCode::Char('k'),
+            down: KeyCode::Char('j'),
+            left: KeyCode::Char('h'),
+            right: KeyCode::Char('l'),
+            quit: KeyCode::Char('q'),
+            reset: KeyCode::Char('r'),
+            pause: KeyCode::Char('p'),
+        }
+    }
+}
+
+/// Command-line settings: key bindings and the speed curve.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct Args {
+    bindings: KeyBindings,
+    curve: SpeedCurve,
+}
+
+impl Args {
+    /// The defaults, overridden by `--<action> <key>` flags and
+    /// `--curve linear|exponential|stepped`. A key is a single character or
+    /// one of `esc`, `enter`, `tab` and `space`.
+    fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let bindings = &mut parsed.bindings;
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or_else(|| format!("`{flag}` needs a value"))?;
+            let slot = match flag.as_str() {
+                "--curve" => {
+                    parsed.curve = parse_curve(&value)
+                        .ok_or_else(|| format!("unknown speed curve `{value}`"))?;
+                    continue;
+                }
+                "--up" => &mut bindings.up,
+                "--down" => &mut bindings.down,
+                "--left" => &mut bindings.left,
+                "--right" => &mut bindings.right,
+                "--quit" => &mut bindings.quit,
+                "--reset" => &mut bindings.reset,
+                "--pause" => &mut bindings.pause,
+                _ => return Err(format!("unknown argument `{flag}`")),
+            };
+            *slot = parse_key(&value).ok_or_else(|| format!("unknown key `{value}`"))?;
+        }
+        Ok(parsed)
+    }
+}
+
+impl KeyBindings {
+
+    /// The action bound to `key`, if any. Ctrl-C always quits; otherwise only
+    /// unmodified presses count, and remapped keys win over the fixed ones.
+    fn action(&self, key: KeyEvent) -> Option<Action> {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Some(Action::Quit);
+        }
+        // Shift is how capitals arrive, so it doesn't count as a modifier here.
+        if !(key.modifiers - KeyModifiers::SHIFT).is_empty() {
+            return None;
+        }
+        let code = key.code;
+        let action = match code {
+            _ if code == self.up => Action::Move(Direction::Up),
+            _ if code == self.down => Action::Move(Direction::Down),
+            _ if code == self.left => Action::Move(Direction::Left),
+            _ if code == self.right => Action::Move(Direction::Right),
+            _ if code == self.quit => Action::Quit,
+            _ if code == self.reset => Action::Reset,
+            _ if code == self.pause => Action::Pause,
+
+            // Nice-to-have: also support arrow keys
+            KeyCode::Up => Action::Move(Direction::Up),
+            KeyCode::Down => Action::Move(Direction::Down),
+            KeyCode::Left => Action::Move(Direction::Left),
+            KeyCode::Right => Action::Move(Direction::Right),
+
+            // Relative steering: turn left/right from the current heading
+            KeyCode::Char('a') => Action::Turn(Turn::Left),
+            KeyCode::Char('d') => Action::Turn(Turn::Right),
+
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+/// A key as written on the command line.
+fn parse_curve(s: &str) -> Option<SpeedCurve> {
+    match s.to_ascii_lowercase().as_str() {
+        "linear" => Some(SpeedCurve::Linear),
+        "exponential" => Some(SpeedCurve::Exponential),
+        "stepped" => Some(SpeedCurve::Stepped),
+        _ => None,
+    }
+}
+
+fn parse_key(s: &str) -> Option<KeyCode> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => return Some(KeyCode::Char(c)),
+        (None, _) => return None,
+        _ => {}
+    }
+    match s.to_ascii_lowercase().as_str() {
+        "esc" => Some(KeyCode::Esc),
+        "enter" => Some(KeyCode::Enter),
+        "tab" => Some(KeyCode::Tab),
+        "space" => Some(KeyCode::Char(' ')),
+        _ => None,
+    }
+}
+
 /// Returns true if the caller should quit.
-fn handle_key(game: &mut GameState, key: KeyEvent) -> bool {
-    match key.code {
-        // Quit keys
-        KeyCode::Char('q') => return true,
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return true,
-
-        // Vim movement (k/j/h/l) → Up/Down/Left/Right
-        KeyCode::Char('k') => game.queue_direction(Direction::Up),
-        KeyCode::Char('j') => game.queue_direction(Direction::Down),
-        KeyCode::Char('h') => game.queue_direction(Direction::Left),
-        KeyCode::Char('l') => game.queue_direction(Direction::Right),
-
-        // Nice-to-have: also support arrow keys
-        KeyCode::Up => game.queue_direction(Direction::Up),
-        KeyCode::Down => game.queue_direction(Direction::Down),
-        KeyCode::Left => game.queue_direction(Direction::Left),
-        KeyCode::Right => game.queue_direction(Direction::Right),
+fn handle_key(game: &mut GameState, key: KeyEvent, bindings: &KeyBindings) -> bool {
+    match bindings.action(key) {
+        Some(Action::Quit) => return true,
+        Some(Action::Move(dir)) => game.queue_direction(dir),
+        Some(Action::Turn(rot)) => game.turn(rot),
 
         // Reset after death
-        KeyCode::Char('r') => {
-            if game.status() == hjkl_snake::GameStatus::Dead {
+        Some(Action::Reset) => {
+            if matches!(
+                game.status(),
+                hjkl_snake::GameStatus::Dead | hjkl_snake::GameStatus::Won
+            ) {
                 game.reset();
             }
         }
 
+        Some(Action::Pause) => game.set_paused(game.status() != GameStatus::Paused),
+
         _ => {}
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn default_bindings_match_hjkl() {
+        let bindings = KeyBindings::default();
+        for c in ['h', 'j', 'k', 'l'] {
+            let dir = Direction::from_vim_char(c).unwrap();
+            assert_eq!(
+                bindings.action(press(KeyCode::Char(c))),
+                Some(Action::Move(dir))
+            );
+        }
+        assert_eq!(
+            bindings.action(press(KeyCode::Char('q'))),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            bindings.action(press(KeyCode::Char('r'))),
+            Some(Action::Reset)
+        );
+        assert_eq!(
+            bindings.action(press(KeyCode::Char('p'))),
+            Some(Action::Pause)
+        );
+        assert_eq!(bindings.action(press(KeyCode::Char('x'))), None);
+    }
+
+    #[test]
+    fn p_toggles_pause() {
+        let cfg = GameConfig::builder().width(10).height(8).build();
+        let mut game = GameState::with_seed(cfg, 1);
+        let bindings = KeyBindings::default();
+        assert!(!handle_key(&mut game, press(KeyCode::Char('p')), &bindings));
+        assert_eq!(game.status(), GameStatus::Paused);
+        let head = game.head();
+        game.tick();
+        assert_eq!(game.head(), head, "paused games don't advance");
+        handle_key(&mut game, press(KeyCode::Char('p')), &bindings);
+        assert_eq!(game.status(), GameStatus::Running);
+    }
+
+    #[test]
+    fn custom_bindings_resolve_to_their_action() {
+        let bindings = KeyBindings {
+            up: KeyCode::Char('w'),
+            down: KeyCode::Char('s'),
+            quit: KeyCode::Esc,
+            ..KeyBindings::default()
+        };
+        let up = bindings.action(press(KeyCode::Char('w')));
+        assert_eq!(up, Some(Action::Move(Direction::Up)));
+        let down = bindings.action(press(KeyCode::Char('s')));
+        assert_eq!(down, Some(Action::Move(Direction::Down)));
+        assert_eq!(bindings.action(press(KeyCode::Esc)), Some(Action::Quit));
+        assert_eq!(bindings.action(press(KeyCode::Char('k'))), None);
+        assert_eq!(bindings.action(press(KeyCode::Char('q'))), None);
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(bindings.action(ctrl_c), Some(Action::Quit));
+    }
+
+    #[test]
+    fn ctrl_c_quits_even_when_c_is_bound() {
+        let bindings = KeyBindings {
+            left: KeyCode::Char('c'),
+            ..KeyBindings::default()
+        };
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(bindings.action(ctrl_c), Some(Action::Quit));
+        let c = bindings.action(press(KeyCode::Char('c')));
+        assert_eq!(c, Some(Action::Move(Direction::Left)));
+    }
+
+    #[test]
+    fn modified_presses_are_not_bindings() {
+        let bindings = KeyBindings::default();
+        for modifiers in [KeyModifiers::CONTROL, KeyModifiers::ALT] {
+            let key = KeyEvent::new(KeyCode::Char('k'), modifiers);
+            assert_eq!(bindings.action(key), None, "{modifiers:?}");
+        }
+        let shifted = KeyEvent::new(KeyCode::Char('K'), KeyModifiers::SHIFT);
+        let bindings = KeyBindings {
+            up: KeyCode::Char('K'),
+            ..KeyBindings::default()
+        };
+        assert_eq!(bindings.action(shifted), Some(Action::Move(Direction::Up)));
+    }
+
+    #[test]
+    fn bindings_load_from_args() {
+        let args = ["--up", "w", "--quit", "esc", "--pause", "space"].map(String::from);
+        let bindings = Args::from_args(args).unwrap().bindings;
+        assert_eq!(bindings.up, KeyCode::Char('w'));
+        assert_eq!(bindings.quit, KeyCode::Esc);
+        assert_eq!(bindings.pause, KeyCode::Char(' '));
+        assert_eq!(bindings.down, KeyBindings::default().down);
+
+        assert!(Args::from_args(["--jump", "x"].map(String::from)).is_err());
+        assert!(Args::from_args(["--up"].map(String::from)).is_err());
+        assert!(Args::from_args(["--up", "nope"].map(String::from)).is_err());
+        assert_eq!(Args::from_args([]), Ok(Args::default()));
+    }
+
+    #[test]
+    fn curve_loads_from_args() {
+        for (name, curve) in [
+            ("linear", SpeedCurve::Linear),
+            ("exponential", SpeedCurve::Exponential),
+            ("Stepped", SpeedCurve::Stepped),
+        ] {
+            let args = Args::from_args(["--curve", name].map(String::from)).unwrap();
+            assert_eq!(args.curve, curve);
+            assert_eq!(args.bindings, KeyBindings::default());
+        }
+        let args = Args::from_args(["--curve", "stepped", "--up", "w"].map(String::from)).unwrap();
+        assert_eq!(args.curve, SpeedCurve::Stepped);
+        assert_eq!(args.bindings.up, KeyCode::Char('w'));
+
+        assert!(Args::from_args(["--curve", "quadratic"].map(String::from)).is_err());
+        assert!(Args::from_args(["--curve"].map(String::from)).is_err());
+        assert_eq!(Args::default().curve, SpeedCurve::Linear);
+    }
+}
@@ -1,59 +1,382 @@
-use super::Raster2D;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+
+use super::{CellGrid, CellKind, Coord, RasterColored};
 
 /// Print raster in simple ascii
-pub fn raster_to_str(raster: &Raster2D) -> String {
-    (0..raster.height)
+pub fn raster_to_str<G: CellGrid + ?Sized>(raster: &G) -> String {
+    (0..raster.height())
         .map(|y| {
             let to_row = |x| {
                 if raster.get(x, y) { '8' } else { '.' }
             };
+            (0..raster.width()).map(to_row).collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Reasons a raster cannot be packed into Braille cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrailleError {
+    /// Braille cells are two dots wide; carries the offending width.
+    WidthNotEven(Coord),
+}
+
+impl fmt::Display for BrailleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WidthNotEven(w) => {
+                write!(f, "cannot render board: width {w} is not a multiple of two")
+            }
+        }
+    }
+}
+
+impl core::error::Error for BrailleError {}
+
+/// Print colored raster in ascii: `^v<>` head (pointing where it faces), `o` body,
+/// `*` food, `#` obstacle, `X` crash site, `.` empty
+pub fn render_ascii_kinds(raster: &RasterColored) -> String {
+    (0..raster.height)
+        .map(|y| {
+            let to_row = |x| match raster.get(x, y) {
+                CellKind::Empty => '.',
+                CellKind::Head(dir) => dir.head_glyph(),
+                CellKind::Body => 'o',
+                CellKind::Food => '*',
+                CellKind::Obstacle => '#',
+                CellKind::Crash => 'X',
+            };
             (0..raster.width).map(to_row).collect::<String>()
         })
         .collect::<Vec<String>>()
         .join("\n")
 }
 
+/// Dot bit for each position in a 2x4 Braille cell, indexed `[row][column]`.
+const BRAILLE_DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// The Braille pattern character with the given dot bits raised.
+fn braille_char(dots: u8) -> char {
+    // U+2800..=U+28FF are all assigned, so this never falls back.
+    char::from_u32(0x2800 | u32::from(dots)).unwrap_or('\u{2800}')
+}
+
+/// Knobs shared by the renderers.
+///
+/// The plain `render_*` functions use [`RenderOptions::default`]; the
+/// `render_*_with` variants take options explicitly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderOptions {
+    /// Mirror rows so y = 0 is drawn at the bottom, for bottom-origin front-ends.
+    pub flip_y: bool,
+}
+
 /// Print raster in braille
-pub fn render_braille(raster: &Raster2D) -> String {
-    if raster.width % 2 != 0 {
-        panic!("Cannot render board if width is not a multiple of two");
+///
+/// Panics if the raster cannot be packed; see [`try_render_braille`].
+pub fn render_braille<G: CellGrid + ?Sized>(raster: &G) -> String {
+    render_braille_with(raster, &RenderOptions::default())
+}
+
+/// Like [`render_braille`], with explicit [`RenderOptions`].
+pub fn render_braille_with<G: CellGrid + ?Sized>(raster: &G, opts: &RenderOptions) -> String {
+    try_render_braille_with(raster, opts).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Print raster in braille, or report why the raster's dimensions don't fit.
+///
+/// Heights that aren't a multiple of four are padded with blank dots, so the
+/// bottom Braille row only uses its upper dot positions.
+pub fn try_render_braille<G: CellGrid + ?Sized>(raster: &G) -> Result<String, BrailleError> {
+    try_render_braille_with(raster, &RenderOptions::default())
+}
+
+/// Like [`try_render_braille`], with explicit [`RenderOptions`].
+pub fn try_render_braille_with<G: CellGrid + ?Sized>(
+    raster: &G,
+    opts: &RenderOptions,
+) -> Result<String, BrailleError> {
+    if raster.width() % 2 != 0 {
+        return Err(BrailleError::WidthNotEven(raster.width()));
     }
-    if raster.height % 4 != 0 {
-        panic!("Cannot render board if height is not a multiple of two");
+
+    let width = (raster.width() / 2) as usize;
+    let height = ((raster.height() + 3) / 4) as usize; // ceil(height/4)
+
+    let mut lines: Vec<Vec<u8>> = vec![vec![0; width]; height];
+    for h in 0..raster.height() {
+        let src_h = if opts.flip_y {
+            raster.height() - 1 - h
+        } else {
+            h
+        };
+        for w in 0..raster.width() {
+            if raster.get(w, src_h) {
+                lines[h as usize / 4][w as usize / 2] |=
+                    BRAILLE_DOTS[h as usize % 4][w as usize % 2];
+            }
+        }
     }
+    Ok(lines
+        .into_iter()
+        .map(|line| line.into_iter().map(braille_char).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
 
-    let width = (raster.width / 2) as usize;
-    let height = (raster.height / 4) as usize;
+/// Print raster with half-block glyphs, packing two rows per line.
+///
+/// An odd final row is treated as if the missing row below it were empty.
+pub fn render_halfblock<G: CellGrid + ?Sized>(raster: &G) -> String {
+    render_halfblock_with(raster, &RenderOptions::default())
+}
 
-    let mut lines: Vec<Vec<[u8; 3]>> = vec![
-        std::iter::repeat([0xe2u8, 0xa0u8, 0x80u8])
-            .take(width)
-            .collect();
-        height
-    ];
-    for h in 0..raster.height {
-        let vert_placement = h as usize % 4;
-        for w in 0..raster.width {
-            let horiz_placement = w as usize % 2;
-            if raster.get(w, h) {
-                let (second, third) = match (vert_placement, horiz_placement) {
-                    (0, 0) => (0b00000000, 0b00000001),
-                    (1, 0) => (0b00000000, 0b00000010),
-                    (2, 0) => (0b00000000, 0b00000100),
-                    (3, 0) => (0b00000001, 0b00000000),
-                    (0, 1) => (0b00000000, 0b00001000),
-                    (1, 1) => (0b00000000, 0b00010000),
-                    (2, 1) => (0b00000000, 0b00100000),
-                    (3, 1) => (0b00000010, 0b00000000),
-                    m => panic!("Unexpected modulo of (%4, %2): {:?}", m),
+/// Like [`render_halfblock`], with explicit [`RenderOptions`].
+pub fn render_halfblock_with<G: CellGrid + ?Sized>(raster: &G, opts: &RenderOptions) -> String {
+    let height = raster.height();
+    let get = |x, y| {
+        let y = if opts.flip_y { height - 1 - y } else { y };
+        // Keep the padding row below the board when flipped, too.
+        (0..height).contains(&y) && raster.get(x, y)
+    };
+    (0..(height + 1) / 2)
+        .map(|row| {
+            let y = row * 2;
+            (0..raster.width())
+                .map(|x| match (get(x, y), get(x, y + 1)) {
+                    (true, true) => '\u{2588}',
+                    (true, false) => '\u{2580}',
+                    (false, true) => '\u{2584}',
+                    (false, false) => ' ',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Truecolor RGB triples used by [`render_ansi`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub head: (u8, u8, u8),
+    pub body: (u8, u8, u8),
+    pub food: (u8, u8, u8),
+    pub obstacle: (u8, u8, u8),
+    pub crash: (u8, u8, u8),
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            head: (255, 215, 0),
+            body: (80, 200, 120),
+            food: (230, 60, 60),
+            obstacle: (128, 128, 128),
+            crash: (255, 0, 255),
+        }
+    }
+}
+
+/// Print colored raster as full blocks with 24-bit ANSI foreground colors.
+/// Every row ends with a reset so colors don't bleed past the board.
+pub fn render_ansi(raster: &RasterColored, palette: &Palette) -> String {
+    (0..raster.height)
+        .map(|y| {
+            let mut row = String::new();
+            for x in 0..raster.width {
+                let (r, g, b) = match raster.get(x, y) {
+                    CellKind::Empty => {
+                        row.push(' ');
+                        continue;
+                    }
+                    CellKind::Head(_) => palette.head,
+                    CellKind::Body => palette.body,
+                    CellKind::Food => palette.food,
+                    CellKind::Obstacle => palette.obstacle,
+                    CellKind::Crash => palette.crash,
                 };
-                lines[h as usize / 4][w as usize / 2][1] |= second;
-                lines[h as usize / 4][w as usize / 2][2] |= third;
+                row.push_str(&format!("\x1b[38;2;{r};{g};{b}m\u{2588}"));
+            }
+            row.push_str("\x1b[0m");
+            row
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Direction, Raster2D};
+
+    /// A diagonal line, computed on the fly rather than stored.
+    struct Diagonal(Coord);
+
+    impl CellGrid for Diagonal {
+        fn width(&self) -> Coord {
+            self.0
+        }
+
+        fn height(&self) -> Coord {
+            self.0
+        }
+
+        fn get(&self, x: Coord, y: Coord) -> bool {
+            x == y && (0..self.0).contains(&x)
+        }
+    }
+
+    #[test]
+    fn renderers_accept_any_cell_grid() {
+        let mut r = Raster2D::new(6, 6);
+        for i in 0..6 {
+            r.set(i, i, true);
+        }
+        let diag = Diagonal(6);
+        assert_eq!(render_braille(&diag), render_braille(&r));
+        assert_eq!(raster_to_str(&diag), raster_to_str(&r));
+        assert_eq!(render_halfblock(&diag), render_halfblock(&r));
+    }
+
+    #[test]
+    fn colored_raster_renders_occupied_cells() {
+        let mut r = RasterColored::new(2, 2);
+        r.set(0, 0, CellKind::Head(Direction::Up));
+        r.set(1, 1, CellKind::Obstacle);
+        assert_eq!(raster_to_str(&r), "8.\n.8");
+    }
+
+    #[test]
+    fn ascii_kinds_uses_distinct_glyphs() {
+        let mut r = RasterColored::new(4, 2);
+        r.set(2, 0, CellKind::Head(Direction::Right));
+        r.set(1, 0, CellKind::Body);
+        r.set(1, 1, CellKind::Body);
+        r.set(3, 1, CellKind::Food);
+        assert_eq!(render_ascii_kinds(&r), ".o>.\n.o.*");
+    }
+
+    #[test]
+    fn odd_width_is_an_error() {
+        let r = Raster2D::new(3, 4);
+        assert_eq!(try_render_braille(&r), Err(BrailleError::WidthNotEven(3)));
+    }
+
+    #[test]
+    fn height_not_multiple_of_four_is_padded() {
+        let mut r = Raster2D::new(40, 25);
+        r.set(0, 24, true);
+        let out = try_render_braille(&r).unwrap();
+        let rows: Vec<&str> = out.lines().collect();
+        assert_eq!(rows.len(), 7);
+        // y=24 is the top dot of the seventh row.
+        assert_eq!(rows[6].chars().next(), Some('\u{2801}'));
+        assert!(
+            rows[..6]
+                .iter()
+                .all(|row| row.chars().all(|c| c == '\u{2800}'))
+        );
+    }
+
+    #[test]
+    fn renders_valid_raster() {
+        let mut r = Raster2D::new(4, 4);
+        r.set(0, 0, true);
+        assert_eq!(try_render_braille(&r).as_deref(), Ok("\u{2801}\u{2800}"));
+        assert_eq!(render_braille(&r), "\u{2801}\u{2800}");
+    }
+
+    #[test]
+    fn braille_dots_map_to_exact_code_points() {
+        let mut r = Raster2D::new(2, 4);
+        // Dots 1, 4 and 8: top-left, top-right, bottom-right.
+        r.set(0, 0, true);
+        r.set(1, 0, true);
+        r.set(1, 3, true);
+        assert_eq!(render_braille(&r), "\u{2889}");
+
+        for y in 0..4 {
+            for x in 0..2 {
+                r.set(x, y, true);
             }
         }
+        assert_eq!(render_braille(&r), "\u{28FF}");
+        assert_eq!(braille_char(0x40), '\u{2840}');
+    }
+
+    #[test]
+    fn flip_y_moves_top_row_to_bottom_braille_row() {
+        let mut r = Raster2D::new(2, 8);
+        r.set(0, 0, true);
+        let flipped = RenderOptions { flip_y: true };
+        let rows: Vec<String> = render_braille_with(&r, &flipped)
+            .lines()
+            .map(String::from)
+            .collect();
+        // y = 0 becomes y = 7: the bottom-left dot (dot 7) of the second row.
+        assert_eq!(rows, ["\u{2800}", "\u{2840}"]);
+        assert_eq!(render_braille(&r), "\u{2801}\n\u{2800}");
+    }
+
+    #[test]
+    fn default_options_render_top_row_first() {
+        let mut r = Raster2D::new(6, 7);
+        for (x, y) in [(0, 0), (1, 2), (5, 6), (3, 3)] {
+            r.set(x, y, true);
+        }
+        let opts = RenderOptions::default();
+        assert_eq!(
+            render_braille_with(&r, &opts),
+            "\u{2821}\u{2880}\u{2800}\n\u{2800}\u{2800}\u{2820}"
+        );
+        assert_eq!(
+            render_halfblock_with(&r, &opts),
+            "\u{2580}     \n \u{2580} \u{2584}  \n      \n     \u{2580}"
+        );
+    }
+
+    #[test]
+    fn halfblock_flip_y_mirrors_rows() {
+        let mut r = Raster2D::new(1, 3);
+        r.set(0, 0, true);
+        let flipped = RenderOptions { flip_y: true };
+        assert_eq!(render_halfblock(&r), "\u{2580}\n ");
+        assert_eq!(render_halfblock_with(&r, &flipped), " \n\u{2580}");
+    }
+
+    #[test]
+    fn halfblock_maps_each_combination() {
+        let mut r = Raster2D::new(4, 2);
+        r.set(0, 0, true);
+        r.set(0, 1, true);
+        r.set(1, 0, true);
+        r.set(2, 1, true);
+        assert_eq!(render_halfblock(&r), "\u{2588}\u{2580}\u{2584} ");
+    }
+
+    #[test]
+    fn halfblock_pads_odd_final_row() {
+        let mut r = Raster2D::new(2, 3);
+        r.set(0, 2, true);
+        r.set(1, 2, true);
+        assert_eq!(render_halfblock(&r), "  \n\u{2580}\u{2580}");
+    }
+
+    #[test]
+    fn ansi_colors_head_and_resets_rows() {
+        let mut r = RasterColored::new(2, 2);
+        r.set(0, 0, CellKind::Head(Direction::Up));
+        let palette = Palette::default();
+        let out = render_ansi(&r, &palette);
+        let (hr, hg, hb) = palette.head;
+        let rows: Vec<&str> = out.lines().collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].starts_with(&format!("\x1b[38;2;{hr};{hg};{hb}m\u{2588}")));
+        assert!(rows.iter().all(|row| row.ends_with("\x1b[0m")));
+        assert_eq!(rows[1], "  \x1b[0m");
     }
-    lines.into_iter().map(|line| {
-        let l = line.into_iter().flatten().collect::<Vec<u8>>();
-        std::str::from_utf8(&l).unwrap().to_owned()
-    }).collect::<Vec<_>>().join("\n")
 }
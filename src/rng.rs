@@ -0,0 +1,85 @@
+use alloc::boxed::Box;
+use core::fmt;
+
+use rand::RngCore;
+use rand_chacha::ChaCha8Rng;
+
+/// Where the game draws its randomness from: the seekable default, or a
+/// caller-supplied source from [`crate::GameState::with_boxed_rng`].
+// Only one lives per game, so keeping the default inline is worth the size.
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum GameRng {
+    ChaCha(ChaCha8Rng),
+    /// Opaque, so its position can't be saved, restored or serialized.
+    Custom(Box<dyn RngCore>),
+}
+
+impl GameRng {
+    /// Position in the output stream; always 0 for a custom source.
+    pub fn word_pos(&self) -> u128 {
+        match self {
+            Self::ChaCha(rng) => rng.get_word_pos(),
+            Self::Custom(_) => 0,
+        }
+    }
+
+    /// Seek to `pos`. Does nothing for a custom source.
+    pub fn set_word_pos(&mut self, pos: u128) {
+        if let Self::ChaCha(rng) = self {
+            rng.set_word_pos(pos);
+        }
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Self::ChaCha(rng) => rng.next_u32(),
+            Self::Custom(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            Self::ChaCha(rng) => rng.next_u64(),
+            Self::Custom(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        match self {
+            Self::ChaCha(rng) => rng.fill_bytes(dst),
+            Self::Custom(rng) => rng.fill_bytes(dst),
+        }
+    }
+}
+
+impl fmt::Debug for GameRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ChaCha(rng) => rng.fmt(f),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+// Saved games keep the plain `ChaCha8Rng` encoding they had before custom
+// sources existed.
+#[cfg(feature = "serde")]
+impl serde::Serialize for GameRng {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::ChaCha(rng) => rng.serialize(serializer),
+            Self::Custom(_) => Err(serde::ser::Error::custom(
+                "a custom RNG can't be serialized",
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for GameRng {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ChaCha8Rng::deserialize(deserializer).map(Self::ChaCha)
+    }
+}
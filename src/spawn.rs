@@ -0,0 +1,122 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::fmt;
+
+use rand::{Rng, RngCore};
+
+use super::{Coord, GameConfig, Point};
+
+/// Decides where the next piece of food goes.
+///
+/// `occupied` holds every cell taken by the snake, existing food, or an
+/// obstacle, plus any ruled out for other reasons such as
+/// [`GameConfig::min_food_distance`]. Returning `None` means there is nowhere
+/// to put food; the game may then ask again with fewer cells ruled out.
+pub trait FoodSpawner: fmt::Debug {
+    fn choose(
+        &mut self,
+        occupied: &BTreeSet<Point>,
+        cfg: &GameConfig,
+        rng: &mut dyn RngCore,
+    ) -> Option<Point>;
+}
+
+/// Any free cell, with equal probability. The default spawner.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UniformSpawner;
+
+impl FoodSpawner for UniformSpawner {
+    fn choose(
+        &mut self,
+        occupied: &BTreeSet<Point>,
+        cfg: &GameConfig,
+        rng: &mut dyn RngCore,
+    ) -> Option<Point> {
+        // Very small grids could be full--avoid inifint loops.
+        let max_attempts = (cfg.width as usize)
+            .saturating_mul(cfg.height as usize)
+            .saturating_mul(2)
+            .max(8);
+        for _ in 0..max_attempts {
+            let x = rng.random_range(0..cfg.width) as Coord;
+            let y = rng.random_range(0..cfg.height) as Coord;
+            let p = Point::new(x, y);
+            if !occupied.contains(&p) {
+                return Some(p);
+            }
+        }
+        None
+    }
+}
+
+/// Free cells on the outermost ring of the board only.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EdgeSpawner;
+
+impl FoodSpawner for EdgeSpawner {
+    fn choose(
+        &mut self,
+        occupied: &BTreeSet<Point>,
+        cfg: &GameConfig,
+        rng: &mut dyn RngCore,
+    ) -> Option<Point> {
+        let (w, h) = (cfg.width, cfg.height);
+        let free: Vec<Point> = (0..h)
+            .flat_map(|y| (0..w).map(move |x| Point::new(x, y)))
+            .filter(|p| p.x == 0 || p.y == 0 || p.x == w - 1 || p.y == h - 1)
+            .filter(|p| !occupied.contains(p))
+            .collect();
+        if free.is_empty() {
+            return None;
+        }
+        Some(free[rng.random_range(0..free.len())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameState;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn edge_spawner_only_uses_border_cells() {
+        let cfg = GameConfig::builder().width(12).height(9).build();
+        for seed in 0..10 {
+            let mut g = GameState::with_seed(cfg.clone(), seed);
+            g.set_food_spawner(alloc::boxed::Box::new(EdgeSpawner));
+            for _ in 0..30 {
+                g.food.clear();
+                g.replenish_food();
+                let p = *g.food_positions().next().unwrap();
+                assert!(
+                    p.x == 0 || p.y == 0 || p.x == 11 || p.y == 8,
+                    "seed {seed}: {p:?} is not on the border"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn edge_spawner_gives_up_when_border_is_full() {
+        let cfg = GameConfig::builder().width(3).height(3).build();
+        let occupied: BTreeSet<Point> = (0..3)
+            .flat_map(|y| (0..3).map(move |x| Point::new(x, y)))
+            .filter(|&p| p != Point::new(1, 1))
+            .collect();
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        assert_eq!(EdgeSpawner.choose(&occupied, &cfg, &mut rng), None);
+    }
+
+    #[test]
+    fn uniform_spawner_avoids_occupied_cells() {
+        let cfg = GameConfig::builder().width(4).height(4).build();
+        let occupied: BTreeSet<Point> = (0..4).map(|x| Point::new(x, 1)).collect();
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        for _ in 0..50 {
+            let p = UniformSpawner.choose(&occupied, &cfg, &mut rng).unwrap();
+            assert!(!occupied.contains(&p), "{p:?}");
+        }
+    }
+}
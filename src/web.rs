@@ -0,0 +1,141 @@
+//! A facade over [`GameState`] that only speaks in integers and byte arrays,
+//! so it can be exported to JavaScript (e.g. via `wasm-bindgen`) without
+//! exposing any Rust-only types.
+
+use alloc::vec::Vec;
+
+use super::{
+    CellKind, Coord, Direction, GameConfig, GameState, GameStatus, rasterize_game_colored,
+};
+
+/// Cell codes returned by [`WebGame::render_cells`].
+pub const CELL_EMPTY: u8 = 0;
+pub const CELL_HEAD: u8 = 1;
+pub const CELL_BODY: u8 = 2;
+pub const CELL_FOOD: u8 = 3;
+pub const CELL_OBSTACLE: u8 = 4;
+pub const CELL_CRASH: u8 = 5;
+
+/// Status codes returned by [`WebGame::tick`] and [`WebGame::status`].
+pub const STATUS_RUNNING: u8 = 0;
+pub const STATUS_PAUSED: u8 = 1;
+pub const STATUS_DEAD: u8 = 2;
+pub const STATUS_WON: u8 = 3;
+
+#[derive(Debug)]
+pub struct WebGame {
+    game: GameState,
+}
+
+impl WebGame {
+    pub fn new(width: Coord, height: Coord, seed: u64) -> Self {
+        let cfg = GameConfig::builder().width(width).height(height).build();
+        Self {
+            game: GameState::with_seed(cfg, seed),
+        }
+    }
+
+    /// Advance one tick and return the resulting status code.
+    pub fn tick(&mut self) -> u8 {
+        self.game.tick();
+        self.status()
+    }
+
+    /// Queue a direction: 0 up, 1 down, 2 left, 3 right. Other values are ignored.
+    pub fn queue_dir(&mut self, dir: u8) {
+        let dir = match dir {
+            0 => Direction::Up,
+            1 => Direction::Down,
+            2 => Direction::Left,
+            3 => Direction::Right,
+            _ => return,
+        };
+        self.game.queue_direction(dir);
+    }
+
+    /// One cell code per board cell, row-major, `width * height` long.
+    pub fn render_cells(&self) -> Vec<u8> {
+        rasterize_game_colored(&self.game)
+            .cells
+            .into_iter()
+            .map(|kind| match kind {
+                CellKind::Empty => CELL_EMPTY,
+                CellKind::Head(_) => CELL_HEAD,
+                CellKind::Body => CELL_BODY,
+                CellKind::Food => CELL_FOOD,
+                CellKind::Obstacle => CELL_OBSTACLE,
+                CellKind::Crash => CELL_CRASH,
+            })
+            .collect()
+    }
+
+    pub fn status(&self) -> u8 {
+        match self.game.status() {
+            GameStatus::Running => STATUS_RUNNING,
+            GameStatus::Paused => STATUS_PAUSED,
+            GameStatus::Dead => STATUS_DEAD,
+            GameStatus::Won => STATUS_WON,
+        }
+    }
+
+    pub fn score(&self) -> u32 {
+        self.game.score()
+    }
+
+    pub fn width(&self) -> Coord {
+        self.game.config().width
+    }
+
+    pub fn height(&self) -> Coord {
+        self.game.config().height
+    }
+
+    pub fn reset(&mut self) {
+        self.game.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn web_game_mirrors_game_state() {
+        let mut web = WebGame::new(10, 8, 42);
+        let mut game = GameState::with_seed(GameConfig::builder().width(10).height(8).build(), 42);
+
+        for dir in [3, 1, 2, 7] {
+            web.queue_dir(dir);
+            if let Some(d) = [
+                Direction::Up,
+                Direction::Down,
+                Direction::Left,
+                Direction::Right,
+            ]
+            .get(dir as usize)
+            {
+                game.queue_direction(*d);
+            }
+            let status = web.tick();
+            game.tick();
+            assert_eq!(status, STATUS_RUNNING);
+            assert_eq!(web.score(), game.score());
+        }
+
+        let cells = web.render_cells();
+        assert_eq!(cells.len(), (web.width() * web.height()) as usize);
+        let head = game.head();
+        assert_eq!(cells[(head.y * web.width() + head.x) as usize], CELL_HEAD);
+        let body = cells.iter().filter(|&&c| c == CELL_BODY).count();
+        assert_eq!(body, game.snake_segments().count() - 1);
+        let food = cells.iter().filter(|&&c| c == CELL_FOOD).count();
+        assert_eq!(food, game.food_positions().count());
+    }
+
+    #[test]
+    fn web_game_reports_death() {
+        let mut web = WebGame::new(4, 4, 1);
+        let statuses: Vec<u8> = (0..4).map(|_| web.tick()).collect();
+        assert_eq!(statuses.last(), Some(&STATUS_DEAD));
+    }
+}